@@ -0,0 +1,9 @@
+use bevy::prelude::Component;
+
+use crate::components::Coordinates;
+
+/// Chunk component, groups the tile meshes and sprites of a square area of the board.
+/// Holds the chunk coordinates, not tile coordinates
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct Chunk(pub Coordinates);
@@ -5,12 +5,13 @@
 use std::fmt::{self,Display,Formatter};
 use std::ops::{Add,Sub};
 use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
 
 // #[cfg_attr(feature = "debug", derive(Inspectable))]
-#[derive(Debug,Default,Copy,Clone,Ord,PartialOrd,Eq,PartialEq,Hash,Component)]
+#[derive(Debug,Default,Copy,Clone,Ord,PartialOrd,Eq,PartialEq,Hash,Component,Serialize,Deserialize)]
 pub struct Coordinates {
-    pub x: u16,
-    pub y: u16,
+    pub x: i32,
+    pub y: i32,
 }
 
 impl Add for Coordinates {
@@ -28,8 +29,8 @@ impl Sub for Coordinates {
 
     fn sub(self, rhs: Self) -> Self::Output {
         Self {
-            x: self.x.saturating_sub(rhs.x),
-            y: self.y.saturating_sub(rhs.y),
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
         }
     }
 }
@@ -44,8 +45,8 @@ impl Add<(i8,i8)> for Coordinates {
     type Output = Self;
 
     fn add(self, (x,y):(i8,i8)) -> Self::Output {
-        let x = ((self.x as i16) + x as i16) as u16;
-        let y = ((self.y as i16) + y as i16) as u16;
+        let x = self.x + x as i32;
+        let y = self.y + y as i32;
 
         Self {x,y}
     }
@@ -0,0 +1,8 @@
+use bevy::prelude::{Component, Timer};
+
+/// Fade out component, shrinks and fades an uncovered tile cover before despawning it
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Clone, Component)]
+pub struct FadeOut {
+    pub timer: Timer,
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::{Component, KeyCode};
+use serde::{Deserialize, Serialize};
+
+/// Keys playing a board, moving its keyboard cursor, uncovering and marking the focused tile
+#[derive(Debug, Clone, Eq, PartialEq, Component, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub reveal: Vec<KeyCode>,
+    pub mark: Vec<KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            reveal: vec![KeyCode::Space, KeyCode::Enter],
+            mark: vec![KeyCode::KeyF],
+            ..Self::arrows()
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Arrow keys, Enter uncovers and right Shift marks
+    pub fn arrows() -> Self {
+        Self {
+            up: KeyCode::ArrowUp,
+            down: KeyCode::ArrowDown,
+            left: KeyCode::ArrowLeft,
+            right: KeyCode::ArrowRight,
+            reveal: vec![KeyCode::Enter],
+            mark: vec![KeyCode::ShiftRight],
+        }
+    }
+
+    /// WASD keys, Space uncovers and E marks
+    pub fn wasd() -> Self {
+        Self {
+            up: KeyCode::KeyW,
+            down: KeyCode::KeyS,
+            left: KeyCode::KeyA,
+            right: KeyCode::KeyD,
+            reveal: vec![KeyCode::Space],
+            mark: vec![KeyCode::KeyE],
+        }
+    }
+}
@@ -2,8 +2,32 @@ pub use cordinates::*;
 pub use bomb::Bomb;
 pub use bomb_neighbor::BombNeighbor;
 pub use uncover::Uncover;
+pub use chunk::Chunk;
+pub use score_text::ScoreText;
+pub use skin_part::SkinPart;
+pub use fade_out::FadeOut;
+pub use particle::Particle;
+pub use number_glyph::NumberGlyph;
+pub use tile_cursor::TileCursor;
+pub use narrator::Narrator;
+pub use pause_overlay::PauseOverlay;
+pub use key_bindings::KeyBindings;
+pub use player::Player;
+pub use remote_board::RemoteBoard;
 
 mod cordinates;
 mod bomb;
 mod bomb_neighbor;
 mod uncover;
+mod chunk;
+mod score_text;
+mod skin_part;
+mod fade_out;
+mod particle;
+mod number_glyph;
+mod tile_cursor;
+mod narrator;
+mod pause_overlay;
+mod key_bindings;
+mod player;
+mod remote_board;
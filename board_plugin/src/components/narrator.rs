@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// Narrator component, marks the accessibility node announcing the narration
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct Narrator;
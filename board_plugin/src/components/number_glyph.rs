@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// Number glyph component, the pip pattern of a bomb counter
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct NumberGlyph;
@@ -0,0 +1,13 @@
+use bevy::prelude::{Component, Timer, Vec2};
+
+/// Particle component, for explosion and win effects
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Clone, Component)]
+pub struct Particle {
+    /// World velocity, in units per second
+    pub velocity: Vec2,
+    /// Downward acceleration, in units per second squared
+    pub gravity: f32,
+    /// Remaining lifetime, the particle fades out along it
+    pub lifetime: Timer,
+}
@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// Pause overlay component, marks the screen hiding the paused board
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct PauseOverlay;
@@ -0,0 +1,8 @@
+use bevy::prelude::Component;
+
+/// Player of a board, numbered from 0 in the `BoardPlayers` order
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Component)]
+pub struct Player {
+    pub index: usize,
+    pub name: String,
+}
@@ -0,0 +1,10 @@
+use bevy::prelude::Component;
+
+/// Remote board component, marks a board played on a server. Its moves are sent to the
+/// server instead of being applied, and its tiles are only known once uncovered
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct RemoteBoard {
+    /// Server round the board was spawned for
+    pub round: u32,
+}
@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// Score text component, marks the UI text displaying the endless board score
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct ScoreText;
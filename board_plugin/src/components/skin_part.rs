@@ -0,0 +1,19 @@
+use bevy::prelude::Component;
+
+/// Skin part component, tells which `BoardAssets` material a board entity uses
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub enum SkinPart {
+    Background,
+    Tile,
+    Cover,
+    Flag,
+    /// Flag on a safe tile, shown once a bomb exploded
+    WrongFlag,
+    Question,
+    Bomb,
+    /// Uncovered bomb
+    ExplodedBomb,
+    /// Bomb counter text or atlas sprite, with its count
+    BombCounter(u8),
+}
@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// Tile cursor component, marks the outline of the keyboard cursor
+// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
+pub struct TileCursor;
@@ -1,6 +1,10 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Component, Timer};
 
-/// Uncover component, indicates a covered tile that should be uncovered
+/// Uncover component, indicates an already uncovered tile whose cover
+/// waits for the cascade animation before being despawned
 // #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
-#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
-pub struct Uncover;
+#[derive(Debug, Clone, Component)]
+pub struct Uncover {
+    /// Remaining time before the cover is despawned
+    pub delay: Timer,
+}
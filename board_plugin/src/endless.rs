@@ -0,0 +1,176 @@
+use bevy::log;
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::components::{Coordinates, ScoreText};
+use crate::events::*;
+use crate::resources::endless_board::EndlessBoard;
+use crate::resources::{BoardAssets, EndlessOptions};
+use crate::spawn::{self, ChunkMaterials, ChunkTile};
+use crate::systems;
+
+/// Endless mode: the board extends as the player explores, with the same
+/// reveal, flag and chord rules as `BoardPlugin`
+pub struct EndlessBoardPlugin<T> {
+    pub running_state: T,
+}
+
+impl<T: States> Plugin for EndlessBoardPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EndlessOptions>();
+        app.add_systems(OnEnter(self.running_state.clone()), Self::create_board);
+        app.add_systems(Update, (
+            systems::endless::input_handling,
+            systems::endless::trigger_event_handler,
+            systems::endless::mark_tiles,
+            systems::endless::update_score_text,
+        ).before(Self::stream_chunks).run_if(in_state(self.running_state.clone())));
+        app.add_systems(Update, (
+            systems::endless::pan_camera,
+            Self::stream_chunks,
+        ).chain().run_if(in_state(self.running_state.clone())));
+        app.add_systems(OnExit(self.running_state.clone()), Self::cleanup_board);
+        app.add_event::<TileTriggerEvent>();
+        app.add_event::<TileMarkEvent>();
+        app.add_event::<BombExplosionEvent>();
+        log::info!("Loaded Endless Board Plugin");
+    }
+}
+
+impl<T> EndlessBoardPlugin<T> {
+    pub fn create_board(
+        mut commands: Commands,
+        options: Res<EndlessOptions>,
+        board_assets: Res<BoardAssets>,
+    ) {
+        let seed = options.seed.unwrap_or_else(|| thread_rng().gen());
+        log::info!("endless board seed:{}", seed);
+        let board_entity = commands
+            .spawn(SpatialBundle::default())
+            .insert(Name::new("Endless Board"))
+            .id();
+        commands
+            .spawn(TextBundle::from_section(
+                "Score: 0",
+                TextStyle {
+                    font: board_assets.bomb_counter_font.clone(),
+                    font_size: 20.,
+                    color: Color::WHITE,
+                },
+            ).with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                left: Val::Px(10.),
+                ..Default::default()
+            }))
+            .insert(Name::new("Score"))
+            .insert(ScoreText);
+        let mut board = EndlessBoard::new(
+            seed,
+            options.bomb_density,
+            options.tile_size,
+            board_entity,
+        );
+        // The origin is always safe
+        board.try_uncover_tile(Coordinates::default());
+        commands.insert_resource(board);
+    }
+
+    /// Spawns the chunks in view of the camera and despawns the others. Every chunk is
+    /// spawned again with the materials of a new skin
+    #[allow(clippy::too_many_arguments)]
+    fn stream_chunks(
+        mut commands: Commands,
+        mut board: ResMut<EndlessBoard>,
+        options: Res<EndlessOptions>,
+        board_assets: Res<BoardAssets>,
+        cameras: Query<(&Camera, &GlobalTransform)>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        atlas_layouts: Res<Assets<TextureAtlasLayout>>,
+        mut chunk_materials: Local<Option<ChunkMaterials>>,
+    ) {
+        if board_assets.is_changed() || chunk_materials.is_none() {
+            *chunk_materials = Some(ChunkMaterials::new(&board_assets, &mut materials, &atlas_layouts));
+            spawn::despawn_endless_chunks(&mut commands, &mut board);
+        }
+        let Some(chunk_materials) = chunk_materials.as_ref() else {
+            return;
+        };
+        let Some((camera, camera_transform)) = cameras.iter().find(|(c, _)| c.is_active) else {
+            return;
+        };
+        let Some(viewport_size) = camera.logical_viewport_size() else {
+            return;
+        };
+        let corners = [Vec2::ZERO, viewport_size]
+            .map(|p| camera.viewport_to_world_2d(camera_transform, p));
+        let [Some(a), Some(b)] = corners else {
+            return;
+        };
+        let in_view = board.chunks_in_view(a.min(b), a.max(b));
+
+        let out_of_view: Vec<Coordinates> = board
+            .chunks
+            .keys()
+            .filter(|c| !in_view.contains(c))
+            .copied()
+            .collect();
+        for chunk in out_of_view {
+            if let Some(entity) = board.remove_chunk(chunk) {
+                log::debug!("Despawning chunk {}", chunk);
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        let missing: Vec<Coordinates> = in_view
+            .into_iter()
+            .filter(|c| !board.chunks.contains_key(c))
+            .collect();
+        let missing: Vec<(Coordinates, Vec<ChunkTile>)> = missing
+            .into_iter()
+            .map(|c| (c, spawn::endless_chunk_tiles(&mut board, c)))
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        let (size, exploded) = ((board.tile_size, options.tile_padding), board.exploded);
+        let mut spawned = Vec::new();
+        commands.entity(board.entity).with_children(|parent| {
+            for (chunk, tiles) in missing {
+                log::debug!("Spawning chunk {}", chunk);
+                let entity = spawn::spawn_chunk(
+                    parent,
+                    chunk,
+                    &tiles,
+                    size,
+                    exploded,
+                    &board_assets,
+                    chunk_materials,
+                    &mut meshes,
+                );
+                spawned.push((chunk, entity));
+            }
+        });
+        board.chunks.extend(spawned);
+    }
+
+    fn cleanup_board(
+        board: Res<EndlessBoard>,
+        mut commands: Commands,
+        score_texts: Query<Entity, With<ScoreText>>,
+        mut cameras: Query<&mut Transform, With<Camera>>,
+    ) {
+        log::info!("endless board score:{}", board.score());
+        commands.entity(board.entity).despawn_recursive();
+        for entity in score_texts.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        // Brings back the camera panned by the player
+        for mut transform in cameras.iter_mut() {
+            transform.translation.x = 0.;
+            transform.translation.y = 0.;
+        }
+        commands.remove_resource::<EndlessBoard>();
+    }
+}
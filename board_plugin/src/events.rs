@@ -1,15 +1,74 @@
-use bevy::prelude::Event;
+use bevy::prelude::{Entity, Event};
 
 use crate::components::Coordinates;
+use crate::resources::tile::Mark;
+use crate::resources::BoardSound;
+
+// Board events carry the board entity, as several boards can be played at once
+
+#[derive(Debug, Copy, Clone, Event)]
+pub struct TileTriggerEvent {
+    pub board: Entity,
+    pub coordinates: Coordinates,
+}
+
+#[derive(Debug, Copy, Clone, Event)]
+pub struct BoardCompletedEvent {
+    pub board: Entity,
+}
+
+#[derive(Debug, Copy, Clone, Event)]
+pub struct BombExplosionEvent {
+    pub board: Entity,
+    pub coordinates: Coordinates,
+}
 
 #[derive(Debug, Copy, Clone, Event)]
-pub struct TileTriggerEvent(pub Coordinates);
+pub struct TileMarkEvent {
+    pub board: Entity,
+    pub coordinates: Coordinates,
+}
 
 #[derive(Debug, Copy, Clone, Event)]
-pub struct BoardCompletedEvent;
+pub struct UndoEvent {
+    pub board: Entity,
+}
 
 #[derive(Debug, Copy, Clone, Event)]
-pub struct BombExplosionEvent;
+pub struct RedoEvent {
+    pub board: Entity,
+}
 
+/// Tiles were uncovered from `origin`, by a reveal or a chord
 #[derive(Debug, Copy, Clone, Event)]
-pub struct TileMarkEvent(pub Coordinates);
+pub struct TilesUncoveredEvent {
+    pub board: Entity,
+    pub origin: Coordinates,
+    pub count: usize,
+    pub chord: bool,
+}
+
+/// The mark of a tile changed
+#[derive(Debug, Copy, Clone, Event)]
+pub struct TileMarkedEvent {
+    pub board: Entity,
+    pub coordinates: Coordinates,
+    pub mark: Option<Mark>,
+}
+
+/// The game timer of a board went through a second, with the elapsed seconds
+#[derive(Debug, Copy, Clone, Event)]
+pub struct TimerTickEvent {
+    pub board: Entity,
+    pub seconds: u64,
+}
+
+#[derive(Debug, Copy, Clone, Event)]
+pub struct BoardSoundEvent(pub BoardSound);
+
+/// Text description of the focused tile or of the game state of a board, for screen readers
+#[derive(Debug, Clone, Event)]
+pub struct NarrationEvent {
+    pub board: Entity,
+    pub text: String,
+}
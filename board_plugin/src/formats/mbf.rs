@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use crate::components::Coordinates;
+use crate::formats::{read_bombs, Bytes, FormatError};
+use crate::resources::tile_map::TileMap;
+use crate::resources::BoardLayout;
+
+/// Reads a Minesweeper Board Format file: the width and height bytes, the big endian
+/// `u16` bomb count, then the `x` and `y` bytes of each bomb, the top row being `y = 0`
+pub fn read_mbf(bytes: &[u8]) -> Result<BoardLayout, FormatError> {
+    let mut bytes = Bytes::new(bytes);
+    let size = (bytes.u8()? as u16, bytes.u8()? as u16);
+    let bombs = bytes.u16()?;
+    read_bombs(&mut bytes, size, bombs)
+}
+
+/// Writes the bombs of a tile map in the Minesweeper Board Format
+pub fn write_mbf(tile_map: &TileMap) -> Result<Vec<u8>, FormatError> {
+    let (Ok(width), Ok(height)) = (u8::try_from(tile_map.width()), u8::try_from(tile_map.height())) else {
+        return Err(FormatError::TooLarge);
+    };
+    let mut bytes = vec![width, height];
+    let mut bombs = Vec::new();
+    for y in (0..height as i32).rev() {
+        for x in 0..width as i32 {
+            if tile_map.is_bomb_at(Coordinates { x, y }) {
+                bombs.extend([x as u8, (height as i32 - 1 - y) as u8]);
+            }
+        }
+    }
+    bytes.extend(((bombs.len() / 2) as u16).to_be_bytes());
+    bytes.extend(bombs);
+    Ok(bytes)
+}
+
+/// Reads a `.mbf` board file
+pub fn load_mbf(path: impl AsRef<Path>) -> Result<BoardLayout, FormatError> {
+    read_mbf(&std::fs::read(path)?)
+}
+
+/// Saves the bombs of a tile map as a `.mbf` board file
+pub fn save_mbf(tile_map: &TileMap, path: impl AsRef<Path>) -> Result<(), FormatError> {
+    Ok(std::fs::write(path, write_mbf(tile_map)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bombs = [Coordinates { x: 0, y: 0 }, Coordinates { x: 3, y: 2 }, Coordinates { x: 1, y: 1 }];
+        let tile_map = TileMap::with_bombs(4, 3, &bombs);
+        let bytes = write_mbf(&tile_map).unwrap();
+        // The top row comes first
+        assert_eq!(bytes, [4, 3, 0, 3, 3, 0, 1, 1, 0, 2]);
+        let layout = read_mbf(&bytes).unwrap();
+        assert_eq!(layout.to_string(), BoardLayout::from(tile_map).to_string());
+    }
+
+    #[test]
+    fn truncated_header() {
+        assert!(matches!(read_mbf(&[]), Err(FormatError::UnexpectedEnd { offset: 0 })));
+        assert!(matches!(read_mbf(&[9, 9, 0]), Err(FormatError::UnexpectedEnd { offset: 2 })));
+        // Fewer bomb positions than announced
+        assert!(matches!(read_mbf(&[9, 9, 0, 2, 1, 1]), Err(FormatError::UnexpectedEnd { offset: 6 })));
+    }
+
+    #[test]
+    fn invalid_boards() {
+        assert!(matches!(
+            read_mbf(&[2, 2, 0, 5]),
+            Err(FormatError::InvalidSize { width: 2, height: 2, bombs: 5 })
+        ));
+        assert!(matches!(
+            read_mbf(&[0, 2, 0, 0]),
+            Err(FormatError::InvalidSize { width: 0, height: 2, bombs: 0 })
+        ));
+        assert!(matches!(
+            read_mbf(&[2, 2, 0, 1, 2, 0]),
+            Err(FormatError::MineOutOfBounds { x: 2, y: 0 })
+        ));
+        assert!(matches!(
+            read_mbf(&[2, 2, 0, 2, 1, 0, 1, 0]),
+            Err(FormatError::MineCountMismatch { expected: 2, found: 1 })
+        ));
+        assert!(matches!(write_mbf(&TileMap::empty(300, 2)), Err(FormatError::TooLarge)));
+    }
+}
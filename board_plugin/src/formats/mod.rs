@@ -0,0 +1,166 @@
+//! Community Minesweeper file formats: `.mbf` boards and `.rmv` replays
+pub use mbf::*;
+pub use rmv::*;
+
+mod mbf;
+mod rmv;
+
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+
+use crate::components::Coordinates;
+use crate::resources::tile_map::TileMap;
+use crate::resources::{BoardLayout, LayoutError};
+
+/// Errors raised while reading or writing a community file
+#[derive(Debug)]
+pub enum FormatError {
+    Io(std::io::Error),
+    /// The file ends inside the field starting at `offset`
+    UnexpectedEnd { offset: usize },
+    /// The file doesn't start with the signature of its format
+    BadMagic,
+    UnsupportedVersion(u16),
+    /// The board has no tile, or more bombs than tiles
+    InvalidSize { width: u16, height: u16, bombs: u16 },
+    MineOutOfBounds { x: u16, y: u16 },
+    /// Fewer bomb positions than announced, some being listed twice
+    MineCountMismatch { expected: u16, found: u16 },
+    UnknownEvent { offset: usize, kind: u8 },
+    /// The board is too large for the format
+    TooLarge,
+    Layout(LayoutError),
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not access file: {}", e),
+            Self::UnexpectedEnd { offset } => write!(f, "File ends at byte {}", offset),
+            Self::BadMagic => write!(f, "Not a file of this format"),
+            Self::UnsupportedVersion(version) => write!(f, "Unsupported version {}", version),
+            Self::InvalidSize { width, height, bombs } => {
+                write!(f, "Invalid board of {}x{} with {} bombs", width, height, bombs)
+            }
+            Self::MineOutOfBounds { x, y } => write!(f, "Bomb at {},{} out of the board", x, y),
+            Self::MineCountMismatch { expected, found } => {
+                write!(f, "{} distinct bombs, expected {}", found, expected)
+            }
+            Self::UnknownEvent { offset, kind } => {
+                write!(f, "Unknown event {} at byte {}", kind, offset)
+            }
+            Self::TooLarge => write!(f, "Board is too large for the format"),
+            Self::Layout(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<std::io::Error> for FormatError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<LayoutError> for FormatError {
+    fn from(e: LayoutError) -> Self {
+        Self::Layout(e)
+    }
+}
+
+/// Reads a board layout, as `.mbf` or as text depending on the extension
+pub fn load_layout(path: impl AsRef<Path>) -> Result<BoardLayout, FormatError> {
+    let path = path.as_ref();
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(e) if e.eq_ignore_ascii_case("mbf") => load_mbf(path),
+        _ => Ok(BoardLayout::load(path)?),
+    }
+}
+
+/// Big endian reader over the bytes of a file
+struct Bytes<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Bytes<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], FormatError> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + len)
+            .ok_or(FormatError::UnexpectedEnd { offset: self.offset })?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// Reader over the next `len` bytes, keeping the file offsets in errors
+    fn section(&mut self, len: usize) -> Result<Bytes<'a>, FormatError> {
+        let offset = self.offset;
+        self.take(len)?;
+        Ok(Self {
+            bytes: &self.bytes[..offset + len],
+            offset,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    fn u8(&mut self) -> Result<u8, FormatError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, FormatError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u24(&mut self) -> Result<u32, FormatError> {
+        let bytes = self.take(3)?;
+        Ok(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, FormatError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+/// Board of `width` by `height` tiles from its bomb positions, the top row first as in
+/// the community formats
+fn read_bombs(
+    bytes: &mut Bytes,
+    (width, height): (u16, u16),
+    bombs: u16,
+) -> Result<BoardLayout, FormatError> {
+    if width == 0 || height == 0 || bombs as u32 > width as u32 * height as u32 {
+        return Err(FormatError::InvalidSize { width, height, bombs });
+    }
+    let mut coordinates = Vec::with_capacity(bombs as usize);
+    for _ in 0..bombs {
+        let (x, y) = (bytes.u8()? as u16, bytes.u8()? as u16);
+        if x >= width || y >= height {
+            return Err(FormatError::MineOutOfBounds { x, y });
+        }
+        let coords = Coordinates {
+            x: x as i32,
+            y: (height - 1 - y) as i32,
+        };
+        if !coordinates.contains(&coords) {
+            coordinates.push(coords);
+        }
+    }
+    if coordinates.len() != bombs as usize {
+        return Err(FormatError::MineCountMismatch {
+            expected: bombs,
+            found: coordinates.len() as u16,
+        });
+    }
+    Ok(TileMap::with_bombs(width, height, &coordinates).into())
+}
@@ -0,0 +1,225 @@
+use std::path::Path;
+
+use crate::components::Coordinates;
+use crate::formats::{read_bombs, Bytes, FormatError};
+use crate::net::{MoveTiming, Replay};
+
+/// Side of a tile in the mouse positions of a replay, in pixels
+const RMV_TILE_SIZE: u16 = 16;
+
+/// Mouse event of a replay video
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum MouseEvent {
+    Move,
+    LeftPress,
+    LeftRelease,
+    RightPress,
+    RightRelease,
+    MiddlePress,
+    MiddleRelease,
+}
+
+/// Reads a Minesweeper Arbiter or Viennasweeper replay, version 1.
+///
+/// The `*rmv` signature and big endian `u16` version are followed by the sizes of the
+/// sections: `u16` result, version info, player info, board, preflags and properties,
+/// `u32` video and `u16` checksum. The sections come next in that order:
+/// - player info: the count of `u8` length prefixed strings, the player name first
+/// - board: width and height bytes, `u16` bomb count and the `x`, `y` bytes of each
+///   bomb, the top row being `y = 0`
+/// - preflags: `u16` count of flagged tiles and their `x`, `y` bytes
+/// - video: mouse events of a kind byte, `u24` milliseconds and `u16` pixel positions,
+///   up to the `0` end event
+///
+/// Clicks are turned into moves as the game handles them: a left release uncovers,
+/// a right press marks, and releasing both buttons or the middle one chords
+pub fn read_rmv(bytes: &[u8]) -> Result<Replay, FormatError> {
+    let mut bytes = Bytes::new(bytes);
+    if bytes.take(4)? != b"*rmv" {
+        return Err(FormatError::BadMagic);
+    }
+    let version = bytes.u16()?;
+    if version != 1 {
+        return Err(FormatError::UnsupportedVersion(version));
+    }
+    let mut sizes = [0; 6];
+    for size in sizes.iter_mut() {
+        *size = bytes.u16()? as usize;
+    }
+    let [result, version_info, player_info, board, preflags, properties] = sizes;
+    let video = bytes.u32()? as usize;
+    let _checksum = bytes.u16()?;
+    bytes.take(result + version_info)?;
+
+    let mut players = bytes.section(player_info)?;
+    let name = match player_info {
+        0 => String::new(),
+        _ => match players.u8()? {
+            0 => String::new(),
+            _ => {
+                let len = players.u8()? as usize;
+                String::from_utf8_lossy(players.take(len)?).trim().to_string()
+            }
+        },
+    };
+
+    let mut board = bytes.section(board)?;
+    let size = (board.u8()? as u16, board.u8()? as u16);
+    let bomb_count = board.u16()?;
+    let mut layout = read_bombs(&mut board, size, bomb_count)?;
+    let height = size.1;
+
+    let mut preflags = bytes.section(preflags)?;
+    if preflags.len() > 0 {
+        for _ in 0..preflags.u16()? {
+            let (x, y) = (preflags.u8()? as u16, preflags.u8()? as u16);
+            if x >= size.0 || y >= height {
+                return Err(FormatError::MineOutOfBounds { x, y });
+            }
+            layout.flags.push(Coordinates { x: x as i32, y: (height - 1 - y) as i32 });
+        }
+    }
+    bytes.take(properties)?;
+
+    let mut video = bytes.section(video)?;
+    let mut moves = Vec::new();
+    let (mut left, mut right, mut chording) = (false, false, false);
+    loop {
+        let offset = video.offset;
+        let event = match video.u8()? {
+            0 => break,
+            1 => MouseEvent::Move,
+            2 => MouseEvent::LeftPress,
+            3 => MouseEvent::LeftRelease,
+            4 => MouseEvent::RightPress,
+            5 => MouseEvent::RightRelease,
+            6 => MouseEvent::MiddlePress,
+            7 => MouseEvent::MiddleRelease,
+            kind => return Err(FormatError::UnknownEvent { offset, kind }),
+        };
+        let millis = video.u24()? as u64;
+        let (x, y) = (video.u16()? / RMV_TILE_SIZE, video.u16()? / RMV_TILE_SIZE);
+        // Clicks outside the board are ignored
+        let coordinates = (x < size.0 && y < height)
+            .then(|| Coordinates { x: x as i32, y: (height - 1 - y) as i32 });
+        let mut push = |mark: bool| {
+            if let Some(coordinates) = coordinates {
+                moves.push(MoveTiming { player: 0, coordinates, mark, millis });
+            }
+        };
+        match event {
+            MouseEvent::Move | MouseEvent::MiddlePress => (),
+            MouseEvent::LeftPress => {
+                left = true;
+                chording = right;
+            }
+            MouseEvent::RightPress => {
+                right = true;
+                chording = left;
+                if !chording {
+                    push(true);
+                }
+            }
+            MouseEvent::LeftRelease | MouseEvent::RightRelease => {
+                let was_left = left;
+                match event {
+                    MouseEvent::LeftRelease => left = false,
+                    _ => right = false,
+                }
+                if chording {
+                    // Releasing the first button chords, the second does nothing
+                    chording = false;
+                    push(false);
+                } else if event == MouseEvent::LeftRelease && was_left {
+                    push(false);
+                }
+            }
+            MouseEvent::MiddleRelease => push(false),
+        }
+    }
+    Ok(Replay { name, layout, moves })
+}
+
+/// Reads a `.rmv` replay file
+pub fn load_rmv(path: impl AsRef<Path>) -> Result<Replay, FormatError> {
+    read_rmv(&std::fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3x3 board with a bomb in its center
+    const BOARD: [u8; 6] = [3, 3, 0, 1, 1, 1];
+
+    /// Replay of a player named "Ann" on `BOARD`, with the given video section
+    fn rmv(video: &[u8], video_size: u32) -> Vec<u8> {
+        let players = [1, 3, b'A', b'n', b'n'];
+        let mut bytes = b"*rmv".to_vec();
+        bytes.extend(1u16.to_be_bytes());
+        for size in [0, 0, players.len(), BOARD.len(), 0, 0] {
+            bytes.extend((size as u16).to_be_bytes());
+        }
+        bytes.extend(video_size.to_be_bytes());
+        bytes.extend(0u16.to_be_bytes());
+        bytes.extend(players);
+        bytes.extend(BOARD);
+        bytes.extend(video);
+        bytes
+    }
+
+    /// Mouse event over the tile `x`, `y` of the replay
+    fn event(kind: u8, millis: u32, x: u16, y: u16) -> Vec<u8> {
+        let mut bytes = vec![kind];
+        bytes.extend(&millis.to_be_bytes()[1..]);
+        bytes.extend((x * RMV_TILE_SIZE + 8).to_be_bytes());
+        bytes.extend((y * RMV_TILE_SIZE + 8).to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn reads_clicks() {
+        let video = [event(2, 100, 0, 0), event(3, 150, 0, 0), event(4, 900, 1, 1), vec![0]].concat();
+        let replay = read_rmv(&rmv(&video, video.len() as u32)).unwrap();
+        assert_eq!(replay.name, "Ann");
+        assert_eq!(replay.layout.to_string(), "...\n.*.\n...\n");
+        let moves: Vec<_> = replay.moves.iter().map(|m| (m.coordinates, m.mark, m.millis)).collect();
+        assert_eq!(
+            moves,
+            [(Coordinates { x: 0, y: 2 }, false, 150), (Coordinates { x: 1, y: 1 }, true, 900)]
+        );
+    }
+
+    #[test]
+    fn bad_header() {
+        assert!(matches!(read_rmv(b"*rm"), Err(FormatError::UnexpectedEnd { offset: 0 })));
+        assert!(matches!(read_rmv(b"*mbf\0\x01"), Err(FormatError::BadMagic)));
+        assert!(matches!(read_rmv(b"*rmv\0\x02"), Err(FormatError::UnsupportedVersion(2))));
+    }
+
+    #[test]
+    fn truncated_events() {
+        // The video section, starting at byte 35, ends inside the millis of its second event
+        let video = [event(2, 100, 0, 0), vec![3, 0, 0]].concat();
+        assert!(matches!(
+            read_rmv(&rmv(&video, video.len() as u32)),
+            Err(FormatError::UnexpectedEnd { offset: 44 })
+        ));
+        // The file ends before the announced video section
+        assert!(matches!(
+            read_rmv(&rmv(&video, 64)),
+            Err(FormatError::UnexpectedEnd { offset: 35 })
+        ));
+        // The events stop without their end event
+        let video = event(2, 100, 0, 0);
+        assert!(matches!(
+            read_rmv(&rmv(&video, video.len() as u32)),
+            Err(FormatError::UnexpectedEnd { offset: 43 })
+        ));
+        let video = [event(9, 100, 0, 0), vec![0]].concat();
+        assert!(matches!(
+            read_rmv(&rmv(&video, video.len() as u32)),
+            Err(FormatError::UnknownEvent { offset: 35, kind: 9 })
+        ));
+    }
+}
@@ -2,27 +2,38 @@ pub mod components;
 pub mod resources;
 mod bounds;
 mod systems;
-mod events;
+pub mod events;
+pub mod states;
+pub mod net;
+pub mod solver;
+pub mod formats;
+mod spawn;
+mod endless;
+
+pub use endless::EndlessBoardPlugin;
 
 use bevy::log;
 use bevy::prelude::*;
-use bevy::utils::hashbrown::HashMap;
-use bevy::window::PrimaryWindow;
+use bevy::window::{PrimaryWindow, WindowResized};
 
-use components::Bomb;
-use components::BombNeighbor;
 use components::Coordinates;
-use components::Uncover;
+use components::SkinPart;
 use resources::board::Board;
-use resources::tile::Tile;
 use resources::BoardOption;
 use resources::TileSize;
 use resources::tile_map::TileMap;
+use spawn::ChunkMaterials;
 use resources::BoardOptions;
 use resources::BoardAssets;
+use resources::{GameStatus, GameTimer, KeyboardCursor, NumberStyle, SoundOptions, Theme, ThemeLoader};
 use bounds::Bounds2;
 use bevy::math::Vec3Swizzles;
 use crate::events::*;
+use components::{Player, RemoteBoard};
+use rand::{thread_rng, Rng};
+use resources::{BoardPlayer, BoardPlayers};
+use net::NetClient;
+use states::{BoardPause, BoardState};
 
 pub struct BoardPlugin<T> {
     pub running_state: T,
@@ -36,62 +47,192 @@ impl<T: States> Plugin for BoardPlugin<T> {
             systems::input::input_handling,
             systems::uncover::trigger_event_handler,
             systems::uncover::uncover_tiles,
+            systems::hover::highlight_tiles,
             systems::mark::mark_tiles,
-        ).run_if(in_state(self.running_state.clone()))); 
+            systems::input::undo_input_handling,
+            systems::input::keyboard_input_handling,
+        ).before(Self::stream_chunks)
+            .run_if(in_state(self.running_state.clone()))
+            .run_if(not(in_state(BoardPause::Paused)))); 
+        app.add_systems(Update, (
+            systems::narration::narrate,
+            systems::narration::announce,
+        ).chain().run_if(in_state(self.running_state.clone()))
+            .run_if(not(in_state(BoardPause::Paused))));
+        app.add_systems(Update, (
+            systems::animation::fade_out_covers,
+            systems::animation::explosion_shockwave,
+            systems::animation::win_effect,
+            systems::animation::update_particles,
+        ).before(Self::stream_chunks)
+            .run_if(in_state(self.running_state.clone())));
+        app.add_systems(Update, (
+            systems::timer::update_timer,
+            systems::sound::board_sounds,
+        ).run_if(in_state(self.running_state.clone()))
+            .run_if(not(in_state(BoardPause::Paused))));
+        app.init_state::<BoardState>();
+        app.add_sub_state::<BoardPause>();
+        app.add_systems(Update, (
+            systems::pause::pause_input,
+            systems::pause::auto_pause,
+        ).run_if(in_state(BoardState::Spawned)));
+        app.add_systems(OnEnter(BoardPause::Paused), systems::pause::show_pause_overlay);
+        app.add_systems(OnExit(BoardPause::Paused), (
+            systems::pause::hide_pause_overlay,
+            systems::pause::consume_resume_click,
+        ));
+        app.init_resource::<SoundOptions>();
+        app.init_resource::<BoardPlayers>();
+        app.add_systems(
+            Update,
+            systems::sound::play_sounds.run_if(resource_exists::<Assets<AudioSource>>),
+        );
+        app.add_systems(Update, (
+            systems::net::send_moves,
+            systems::net::send_cursor,
+            systems::net::receive_messages,
+        ).chain().before(Self::stream_chunks)
+            .run_if(resource_exists::<NetClient>)
+            .run_if(in_state(self.running_state.clone())));
+        app.add_systems(Update, (
+            Self::relayout_board,
+            systems::history::history_event_handler,
+            Self::stream_chunks,
+            systems::narration::draw_cursor,
+            systems::status::update_status,
+        ).chain().run_if(in_state(self.running_state.clone())));
         // app.add_systems(Update , systems::uncover::trigger_event_handler);
         // app.add_systems(Update (self.running_state), systems::uncover::uncover_tiles);
         app.add_systems(OnExit(self.running_state.clone()), Self::cleanup_board);
+        app.init_asset::<Theme>();
+        app.register_asset_loader(ThemeLoader);
+        app.init_resource::<NumberStyle>();
+        app.add_systems(Update, (
+            systems::theme::apply_theme,
+            systems::number_style::style_numbers,
+        ).chain());
         app.add_event::<TileTriggerEvent>();
         app.add_event::<TileMarkEvent>();
         app.add_event::<BombExplosionEvent>();
         app.add_event::<BoardCompletedEvent>();
+        app.add_event::<UndoEvent>();
+        app.add_event::<RedoEvent>();
+        app.add_event::<TilesUncoveredEvent>();
+        app.add_event::<TileMarkedEvent>();
+        app.add_event::<TimerTickEvent>();
+        app.add_event::<BoardSoundEvent>();
+        app.add_event::<NarrationEvent>();
         log::info!("Loaded Board Plugin");
     }
 }
 
 impl<T> BoardPlugin<T> {
+    /// Spawns a board for each of the `BoardPlayers`, side by side, or the
+    /// remote board of the `NetClient` when connected to a server
+    #[allow(clippy::too_many_arguments)]
     pub fn create_borad(
         mut commands: Commands,
         board_options: Option<Res<BoardOptions>>,
+        board_players: Res<BoardPlayers>,
         board_assets: Res<BoardAssets>,
+        net_client: Option<Res<NetClient>>,
         window: Query<&Window, With<PrimaryWindow>>,
+        mut board_state: ResMut<NextState<BoardState>>,
+        mut board_pause: ResMut<NextState<BoardPause>>,
     ){
-        let options = match board_options {
-            Some(o) => o.clone(),
-            None => todo!(),
+        let Some(options) = board_options else {
+            log::error!("No board options to create the board from");
+            return;
         };
-        let mut tile_map = TileMap::empty(options.map_size.0, options.map_size.1);
-        tile_map.set_bombs(options.bomb_count);
+        let window = window.single();
+        match net_client.as_ref().and_then(|c| c.welcome.as_ref()) {
+            Some(welcome) => {
+                let (width, height) = welcome.map_size;
+                let player = BoardPlayer {
+                    name: welcome.name.clone(),
+                    keys: Default::default(),
+                };
+                let board = Board::headless(TileMap::hidden(width, height, welcome.bomb_count));
+                Self::spawn_board(
+                    &mut commands,
+                    &options,
+                    &board_assets,
+                    window,
+                    board,
+                    (0, 1),
+                    &player,
+                    Some(RemoteBoard { round: welcome.round }),
+                );
+            }
+            None => {
+                let layout = options.layout.as_ref().and_then(|path| {
+                    formats::load_layout(path)
+                        .inspect_err(|e| log::error!("Failed to load {}: {}", path.display(), e))
+                        .ok()
+                });
+                // Every board is generated from the same seed
+                let seed = options.seed.unwrap_or_else(|| thread_rng().gen());
+                log::info!("board seed:{}", seed);
+                for (index, player) in board_players.0.iter().enumerate() {
+                    let board = match layout.clone() {
+                        Some(layout) => layout.into_board(),
+                        None => Board::random(&options, seed),
+                    };
+                    Self::spawn_board(
+                        &mut commands,
+                        &options,
+                        &board_assets,
+                        window,
+                        board,
+                        (index, board_players.0.len()),
+                        player,
+                        None,
+                    );
+                }
+            }
+        }
+        board_state.set(BoardState::Spawned);
+        // A restart keeps the board state, so the pause has to be lifted too
+        board_pause.set(BoardPause::Running);
+    }
+
+    /// Spawns a headless board for a player in the `column` of `columns`, returning the
+    /// board entity. Remote boards are marked
+    #[allow(clippy::too_many_arguments)]
+    fn spawn_board(
+        commands: &mut Commands,
+        options: &BoardOptions,
+        board_assets: &BoardAssets,
+        window: &Window,
+        mut board: Board,
+        (column, columns): (usize, usize),
+        player: &BoardPlayer,
+        remote: Option<RemoteBoard>,
+    ) -> Entity {
+        let tile_map = &board.tile_map;
         #[cfg(feature = "debug")]
         log::info!("{}",tile_map.console_output());
 
-        let tile_size = match options.tile_size {
-            TileSize::Fixed(v) => v,
-            TileSize::Adaptive { min, max } => Self::adaptive_tile_size(
-                window, 
-                (min, max),
-                (tile_map.width(), tile_map.height()),
-            ),
-        };
+        let tile_size = Self::tile_size(
+            options,
+            window,
+            (tile_map.width(), tile_map.height()),
+            columns,
+        );
 
         let board_size = Vec2::new(
             tile_map.width() as f32 * tile_size,
             tile_map.height() as f32 * tile_size,
         );
         log::info!("board size:{}", board_size);
-        let board_position = match  options.position {
-            BoardOption::Centerd { offset } => {
-                Vec3::new(-(board_size.x / 2.), -(board_size.y / 2.), 0.) + offset
-            }
-            BoardOption::Custom(p) => p,
-        };
-        let mut covered_tiles = HashMap::with_capacity((tile_map.width() * tile_map.height()).into()); 
-        let mut safe_start = None;
+        let board_position = Self::board_position(options, (board_size, tile_size), window, (column, columns));
         let board_entity = commands
             .spawn_empty()
-            .insert(Name::new("Board"))
+            .insert(Name::new(format!("Board {}", player.name)))
             .insert(Transform::from_translation(board_position))
             .insert(GlobalTransform::default())
+            .insert(VisibilityBundle::default())
             .with_children(|parent|{
                 parent
                     .spawn(SpriteBundle{
@@ -104,150 +245,223 @@ impl<T> BoardPlugin<T> {
                         transform: Transform::from_xyz(board_size.x / 2., board_size.y / 2., 0.),
                         ..Default::default()
                     })
-                    .insert(Name::new("Background"));
-                Self::spawn_tile(
-                    parent,
-                    &tile_map,
-                    tile_size,
-                    options.tile_padding,
-                    &board_assets,
-                    &mut covered_tiles,
-                    &mut safe_start,
-            )
+                    .insert(Name::new("Background"))
+                    .insert(SkinPart::Background);
+                parent.spawn(systems::narration::cursor_bundle());
+                parent.spawn(systems::narration::narrator_bundle());
             })
             .id();
-            if options.safe_start {
-                if let Some(entity) = safe_start {
-                    commands.entity(entity).insert(Uncover);
+        board.bounds = Bounds2 {
+            position: board_position.xy(),
+            size: board_size,
+        };
+        board.tile_size = tile_size;
+        board.entity = board_entity;
+        let cursor = Coordinates {
+            x: board.tile_map.width() as i32 / 2,
+            y: board.tile_map.height() as i32 / 2,
+        };
+        commands.entity(board_entity).insert((
+            board,
+            GameTimer::default(),
+            GameStatus::default(),
+            KeyboardCursor { coordinates: cursor },
+            player.keys.clone(),
+            Player {
+                index: column,
+                name: player.name.clone(),
+            },
+        ));
+        if let Some(remote) = remote {
+            commands.entity(board_entity).insert(remote);
+        }
+        board_entity
+    }
+
+    /// Spawns the chunks in view of the camera and despawns the others,
+    /// so only the visible part of the boards has entities. Chunks are spawned again
+    /// with the materials of a new skin
+    #[allow(clippy::too_many_arguments)]
+    fn stream_chunks(
+        mut commands: Commands,
+        mut boards: Query<&mut Board>,
+        board_options: Res<BoardOptions>,
+        board_assets: Res<BoardAssets>,
+        cameras: Query<(&Camera, &GlobalTransform)>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        atlas_layouts: Res<Assets<TextureAtlasLayout>>,
+        mut chunk_materials: Local<Option<ChunkMaterials>>,
+    ) {
+        if board_assets.is_changed() || chunk_materials.is_none() {
+            *chunk_materials = Some(ChunkMaterials::new(&board_assets, &mut materials, &atlas_layouts));
+            for mut board in boards.iter_mut() {
+                spawn::despawn_chunks(&mut commands, &mut board);
+            }
+        }
+        let Some(chunk_materials) = chunk_materials.as_ref() else {
+            return;
+        };
+        let Some((camera, camera_transform)) = cameras.iter().find(|(c, _)| c.is_active) else {
+            return;
+        };
+        let Some(viewport_size) = camera.logical_viewport_size() else {
+            return;
+        };
+        let corners = [Vec2::ZERO, viewport_size]
+            .map(|p| camera.viewport_to_world_2d(camera_transform, p));
+        let [Some(a), Some(b)] = corners else {
+            return;
+        };
+        for mut board in boards.iter_mut() {
+            let in_view = board.chunks_in_view(a.min(b), a.max(b));
+
+            let out_of_view: Vec<Coordinates> = board
+                .chunks
+                .keys()
+                .filter(|c| !in_view.contains(c))
+                .copied()
+                .collect();
+            for chunk in out_of_view {
+                if let Some(entity) = board.remove_chunk(chunk) {
+                    log::debug!("Despawning chunk {}", chunk);
+                    commands.entity(entity).despawn_recursive();
                 }
             }
-        commands.insert_resource(Board {
-            tile_map,
-            bounds: Bounds2 {
-                position: board_position.xy(),
-                size: board_size,
-            },
-            tile_size,
-            covered_tiles,
-            entity: board_entity,
-            marked_tiles: Vec::new(),
-        });
+
+            let missing: Vec<Coordinates> = in_view
+                .into_iter()
+                .filter(|c| !board.chunks.contains_key(c))
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            let exploded = board.is_exploded();
+            let mut spawned = Vec::new();
+            commands.entity(board.entity).with_children(|parent| {
+                for chunk in missing {
+                    log::debug!("Spawning chunk {}", chunk);
+                    let tiles = spawn::board_chunk_tiles(&board, chunk);
+                    let entity = spawn::spawn_chunk(
+                        parent,
+                        chunk,
+                        &tiles,
+                        (board.tile_size, board_options.tile_padding),
+                        exploded,
+                        &board_assets,
+                        chunk_materials,
+                        &mut meshes,
+                    );
+                    spawned.push((chunk, entity));
+                }
+            });
+            board.chunks.extend(spawned);
+        }
     }
 
-    fn bomb_count_text_bundle(count: u8, board_assets: &BoardAssets, size: f32) -> Text2dBundle{
-        // We generate a text bundle
-        let color = board_assets.bomb_counter_colors(count);
-        Text2dBundle {
-            text: Text {
-                sections: vec![TextSection {
-                    value: count.to_string(),
-                    style: TextStyle {
-                        color,
-                        font: board_assets.bomb_counter_font.clone(),
-                        font_size: size,
-                    },
-                }],
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(0., 0., 1.),
-            ..Default::default()
+    /// Tile world size for the given window split in `columns`, following `options.tile_size`
+    fn tile_size(
+        options: &BoardOptions,
+        window: &Window,
+        (width, height): (u16, u16),
+        columns: usize,
+    ) -> f32 {
+        match options.tile_size {
+            TileSize::Fixed(v) => v,
+            TileSize::Adaptive { min, max } => Self::adaptive_tile_size(
+                window,
+                (min, max),
+                (width, height),
+                columns,
+            ),
         }
     }
 
-    fn spawn_tile(
-        parent: &mut ChildBuilder,
-        tile_map: &TileMap,
-        size: f32,
-        padding: f32,
-        board_assets: &BoardAssets,
-        covered_tiles: &mut HashMap<Coordinates, Entity>,
-        safe_start_entity: &mut Option<Entity>,
-    ) {
-        for (y, line) in tile_map.iter().enumerate(){
-            for(x, tile) in line.iter().enumerate(){
-                let coordinates = Coordinates {
-                    x: x as u16,
-                    y: (tile_map.height() - 1) - (y as u16),
-                };
-                let mut cmd = parent.spawn_empty();
-                cmd.insert(SpriteBundle {
-                    sprite: Sprite {
-                        color: board_assets.tile_material.color,
-                        custom_size: Some(Vec2::splat(size - padding)),
-                        ..Default::default()
-                    },
-                    transform: Transform::from_xyz(
-                        (x as f32 * size) + (size / 2.),
-                        (y as f32 * size) + (size / 2.),
-                        1.,
-                    ),
-                    texture: board_assets.board_material.texture.clone(),
-                    ..Default::default()
-                })
-                .insert(Name::new(format!("Tile ({}, {})",x, y)))
-                .insert(coordinates)
-                .with_children(|parent| {
-                    let entity = parent
-                        .spawn(SpriteBundle {
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::splat(size - padding)),
-                                color: board_assets.covered_tile_material.color,
-                                ..Default::default()
-                            },
-                            transform: Transform::from_xyz(0., 0., 2.),
-                            ..Default::default()
-                        })
-                        .insert(Name::new("Tile Cover"))
-                        .id();
-                    covered_tiles.insert(coordinates, entity);
-                    if safe_start_entity.is_none() && *tile == Tile::Empty {
-                        *safe_start_entity = Some(entity);
-                    }
-                });
-                match tile {
-                    Tile::Bomb => {
-                        cmd.insert(Bomb);
-                        cmd.with_children(|parent| {
-                            parent.spawn(SpriteBundle {
-                                sprite: Sprite {
-                                    custom_size: Some(Vec2::splat(size - padding)),
-                                    ..Default::default()
-                                },
-                                transform: Transform::from_xyz(0., 0., 1.),
-                                texture: board_assets.bomb_material.texture.clone(),
-                                ..Default::default()
-                            });
-                        });
-                    }
-                    Tile::BombNeighbor(v) => {
-                        cmd.insert(BombNeighbor {count: *v});
-                        cmd.with_children(|parent| {
-                            parent.spawn(Self::bomb_count_text_bundle(
-                                *v,
-                                board_assets,
-                                size - padding,
-                            ));
-                        });
-                    }
-                    Tile::Empty => (),
-                }
+    /// Board world position for a board of `board_size`, with tiles of `tile_size`,
+    /// in the `column` of `columns`, following `options.position`
+    fn board_position(
+        options: &BoardOptions,
+        (board_size, tile_size): (Vec2, f32),
+        window: &Window,
+        (column, columns): (usize, usize),
+    ) -> Vec3 {
+        match options.position {
+            BoardOption::Centerd { offset } => {
+                let column_width = window.width() / columns as f32;
+                let center_x = -(window.width() / 2.) + column_width * (column as f32 + 0.5);
+                Vec3::new(center_x - (board_size.x / 2.), -(board_size.y / 2.), 0.) + offset
             }
+            // Next boards are laid out on the right, one tile apart
+            BoardOption::Custom(p) => p + Vec3::X * (board_size.x + tile_size) * column as f32,
         }
     }
 
     fn adaptive_tile_size (
-        q_window: Query<&Window, With<PrimaryWindow>>,
+        window: &Window,
         (min, max): (f32, f32),
         (width, height): (u16, u16),
+        columns: usize,
     ) -> f32{
-        let window = q_window.single();
-        let max_width = window.width() / width as f32;
+        let max_width = window.width() / columns as f32 / width as f32;
         let max_height = window.height() / height as f32;
         max_width.min(max_height).clamp(min, max)
     }
 
-    fn cleanup_board(board: Res<Board>, mut commands: Commands) {
-        commands.entity(board.entity).despawn_recursive();
-        commands.remove_resource::<Board>();
+    /// Recomputes tile size and board position when the window is resized
+    /// or the options change, spawning the chunks again at the new tile size
+    /// instead of regenerating the boards
+    fn relayout_board(
+        mut commands: Commands,
+        mut resize_evr: EventReader<WindowResized>,
+        mut boards: Query<(&mut Board, &mut Transform, &Player, &Children)>,
+        board_options: Res<BoardOptions>,
+        window: Query<&Window, With<PrimaryWindow>>,
+        mut sprites: Query<(&SkinPart, &mut Sprite, &mut Transform), Without<Board>>,
+    ) {
+        // Themes may change the tile padding
+        if resize_evr.read().last().is_none() && !board_options.is_changed() {
+            return;
+        }
+        let window = match window.get_single() {
+            Ok(w) => w,
+            Err(e) => {
+                log::error!("Failed to retrieve primary window: {}", e);
+                return;
+            }
+        };
+        let columns = boards.iter().count();
+        for (mut board, mut board_transform, player, board_children) in boards.iter_mut() {
+            let (width, height) = (board.tile_map.width(), board.tile_map.height());
+            let tile_size = Self::tile_size(&board_options, window, (width, height), columns);
+            let board_size = Vec2::new(width as f32 * tile_size, height as f32 * tile_size);
+            let board_position =
+                Self::board_position(&board_options, (board_size, tile_size), window, (player.index, columns));
+            log::info!("board relayout, size:{}", board_size);
+
+            board_transform.translation = board_position;
+            for child in board_children.iter() {
+                if let Ok((SkinPart::Background, mut sprite, mut transform)) = sprites.get_mut(*child) {
+                    sprite.custom_size = Some(board_size);
+                    transform.translation = Vec3::new(board_size.x / 2., board_size.y / 2., 0.);
+                }
+            }
+            spawn::despawn_chunks(&mut commands, &mut board);
+            board.tile_size = tile_size;
+            board.bounds = Bounds2 {
+                position: board_position.xy(),
+                size: board_size,
+            };
+        }
+    }
+
+    fn cleanup_board(
+        boards: Query<Entity, With<Board>>,
+        mut commands: Commands,
+        mut board_state: ResMut<NextState<BoardState>>,
+    ) {
+        for entity in boards.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        board_state.set(BoardState::Despawned);
     }
 }
@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bevy::log;
+use bevy::prelude::Resource;
+
+use crate::components::Coordinates;
+use crate::net::protocol::*;
+use crate::net::Replay;
+
+/// Connection to a game server, or replay file followed by a spectator. While it exists,
+/// the board is spawned as a `RemoteBoard` from the `welcome` of the server
+#[derive(Debug, Resource)]
+pub struct NetClient {
+    /// Current round, once joined
+    pub welcome: Option<Welcome>,
+    /// Scoreboard of the server
+    pub players: Vec<PlayerInfo>,
+    /// Moves played on the board this round, for spectators
+    pub moves: Vec<MoveTiming>,
+    /// Is the board only watched
+    pub spectator: bool,
+    /// Was the connection lost
+    pub disconnected: bool,
+    /// Connection to the server, `None` for a replay
+    stream: Option<TcpStream>,
+    incoming: Mutex<Receiver<ServerMessage>>,
+    /// Board messages of the current round, applied once the board is spawned
+    pub(crate) pending: VecDeque<ServerMessage>,
+    /// Exploded bomb, announced on the next frame once the bombs are spawned
+    pub(crate) exploded: Option<Coordinates>,
+}
+
+impl NetClient {
+    /// Connects to a server and joins it as `name`
+    pub fn connect(address: impl ToSocketAddrs, name: &str) -> io::Result<Self> {
+        let join = ClientMessage::Join { name: name.to_string() };
+        Self::open(address, &join, false)
+    }
+
+    /// Connects to a server to watch the board of a player, by scoreboard index
+    pub fn spectate(address: impl ToSocketAddrs, player: usize) -> io::Result<Self> {
+        Self::open(address, &ClientMessage::Spectate { player }, true)
+    }
+
+    /// Watches a replay file written by the server, following it as it grows
+    pub fn tail(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let (message_tx, message_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut line = String::new();
+            loop {
+                match reader.read_line(&mut line) {
+                    // Waits for the server to write the rest of the line
+                    Ok(_) if !line.ends_with('\n') => thread::sleep(Duration::from_millis(100)),
+                    Ok(_) => {
+                        let message = ron::from_str(&line)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+                        line.clear();
+                        if !forward(message, &message_tx) {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to read the replay: {}", e);
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(Self::new(None, message_rx, true))
+    }
+
+    /// Watches a replay of another program, its moves played at their recorded time
+    pub fn replay(replay: &Replay) -> Self {
+        let messages = replay.messages();
+        let (message_tx, message_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let started = Instant::now();
+            for message in messages {
+                if let ServerMessage::Moved(timing) = &message {
+                    let at = Duration::from_millis(timing.millis);
+                    thread::sleep(at.saturating_sub(started.elapsed()));
+                }
+                if message_tx.send(message).is_err() {
+                    return;
+                }
+            }
+        });
+        Self::new(None, message_rx, true)
+    }
+
+    fn open(address: impl ToSocketAddrs, first: &ClientMessage, spectator: bool) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        write_message(&mut stream, first)?;
+        let reader = stream.try_clone()?;
+        let (message_tx, message_rx) = mpsc::channel();
+        thread::spawn(move || {
+            for message in read_messages::<ServerMessage>(reader) {
+                if !forward(message, &message_tx) {
+                    return;
+                }
+            }
+        });
+        Ok(Self::new(Some(stream), message_rx, spectator))
+    }
+
+    fn new(stream: Option<TcpStream>, incoming: Receiver<ServerMessage>, spectator: bool) -> Self {
+        Self {
+            welcome: None,
+            players: Vec::new(),
+            moves: Vec::new(),
+            spectator,
+            disconnected: false,
+            stream,
+            incoming: Mutex::new(incoming),
+            pending: VecDeque::new(),
+            exploded: None,
+        }
+    }
+
+    /// Sends a message to the server, spectators only watch
+    pub fn send(&mut self, message: &ClientMessage) {
+        let Some(stream) = self.stream.as_mut().filter(|_| !self.spectator) else {
+            return;
+        };
+        if let Err(e) = write_message(stream, message) {
+            log::error!("Failed to send {:?}: {}", message, e);
+            self.disconnected = true;
+        }
+    }
+
+    /// Reads the messages received so far. Round and scoreboard messages are kept,
+    /// board messages are queued until applied to the board
+    pub fn receive(&mut self) {
+        let Ok(incoming) = self.incoming.get_mut() else {
+            return;
+        };
+        loop {
+            match incoming.try_recv() {
+                Ok(ServerMessage::Welcome(welcome)) => {
+                    log::info!("Joined round {} as {}", welcome.round, welcome.name);
+                    self.welcome = Some(welcome);
+                    // The scoreboard of the last round would end the new one
+                    self.players.clear();
+                    self.moves.clear();
+                    self.pending.clear();
+                    self.exploded = None;
+                }
+                Ok(ServerMessage::Players(players)) => self.players = players,
+                Ok(ServerMessage::Moved(timing)) => self.moves.push(timing),
+                Ok(ServerMessage::Error(e)) => log::warn!("Server error: {}", e),
+                Ok(message) => self.pending.push_back(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // A replay stays on its last move
+                    self.disconnected = self.stream.is_some();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Forwards a read message to the client, returning whether to go on reading
+fn forward(message: io::Result<ServerMessage>, message_tx: &Sender<ServerMessage>) -> bool {
+    match message {
+        Ok(message) => message_tx.send(message).is_ok(),
+        Err(e) => {
+            log::warn!("Connection lost: {}", e);
+            false
+        }
+    }
+}
+
+impl Drop for NetClient {
+    fn drop(&mut self) {
+        if let Some(stream) = &self.stream {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
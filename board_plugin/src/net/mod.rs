@@ -0,0 +1,14 @@
+//! Client/server multiplayer over TCP. The server owns the boards and applies the moves
+//! of its clients, which only learn the tiles they uncover
+pub use client::NetClient;
+pub use protocol::*;
+pub use replay::Replay;
+pub use server::{GameServer, ServerOptions};
+
+mod client;
+mod protocol;
+mod replay;
+mod server;
+
+/// Default port of the game server
+pub const DEFAULT_PORT: u16 = 7878;
@@ -0,0 +1,118 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::components::Coordinates;
+use crate::resources::tile::{Mark, Tile};
+use crate::resources::GameStatus;
+
+/// Multiplayer mode of a server
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum NetMode {
+    /// Every player plays the same board
+    #[default]
+    Coop,
+    /// Each player plays their own board, generated from the same seed,
+    /// the first to clear it wins
+    Race,
+}
+
+/// Message sent by a client to the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// First message of a client
+    Join { name: String },
+    /// Uncovers or chords a tile, as a `TileTriggerEvent`
+    Trigger(Coordinates),
+    /// Marks a tile, as a `TileMarkEvent`
+    Mark(Coordinates),
+    /// Asks for a new round, once the current one is over
+    NewGame,
+    /// First message of a spectator, watching the board of a player by scoreboard index
+    Spectate { player: usize },
+    /// Tile under the mouse or keyboard cursor, shown to the spectators
+    Cursor(Coordinates),
+}
+
+/// Round started by the server, sent to each player when joining and on new rounds.
+/// Spectators receive the welcome of the watched player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Welcome {
+    /// Index of the player in the `Players` scoreboard
+    pub player: usize,
+    pub name: String,
+    pub mode: NetMode,
+    pub round: u32,
+    pub map_size: (u16, u16),
+    pub bomb_count: u16,
+}
+
+/// Scoreboard line of a player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub status: GameStatus,
+    /// Safe tiles left to uncover
+    pub tiles_left: usize,
+    pub seconds: u64,
+}
+
+/// Move played on a board, with its time since the first move of the game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoveTiming {
+    /// Scoreboard index of the player
+    pub player: usize,
+    pub coordinates: Coordinates,
+    /// Was the tile marked, or triggered
+    pub mark: bool,
+    pub millis: u64,
+}
+
+/// Message sent by the server to a client. Board messages are only sent to
+/// the players of the board, and never hold covered tiles but the bombs of a lost game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Welcome(Welcome),
+    /// Tiles uncovered from `origin` with their content. Without origin, the tiles
+    /// uncovered before joining
+    Uncovered {
+        tiles: Vec<(Coordinates, Tile)>,
+        origin: Option<Coordinates>,
+        chord: bool,
+    },
+    Marked {
+        coordinates: Coordinates,
+        mark: Option<Mark>,
+    },
+    /// A bomb exploded, with every bomb of the board now that the game is lost
+    Exploded {
+        coordinates: Coordinates,
+        bombs: Vec<Coordinates>,
+    },
+    Completed,
+    /// A move was played, sent before its board messages
+    Moved(MoveTiming),
+    /// Cursor of a player, by scoreboard index
+    Cursor {
+        player: usize,
+        coordinates: Coordinates,
+    },
+    Players(Vec<PlayerInfo>),
+    /// The last message was refused
+    Error(String),
+}
+
+/// Writes a message as a line of RON
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let line = ron::to_string(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    writeln!(writer, "{}", line)?;
+    writer.flush()
+}
+
+/// Reads the messages written by `write_message`, until the end of the stream
+pub fn read_messages<T: DeserializeOwned>(reader: impl Read) -> impl Iterator<Item = io::Result<T>> {
+    BufReader::new(reader).lines().map(|line| {
+        ron::from_str(&line?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    })
+}
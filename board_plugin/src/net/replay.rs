@@ -0,0 +1,39 @@
+use crate::net::protocol::*;
+use crate::net::server::ServerBoard;
+use crate::resources::BoardLayout;
+
+/// Game recorded by another program, played back as the replay stream of a spectator
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// Name of the player
+    pub name: String,
+    /// Board at the start of the game
+    pub layout: BoardLayout,
+    /// Moves of the player, in order. Moves changing nothing are skipped on playback
+    pub moves: Vec<MoveTiming>,
+}
+
+impl Replay {
+    /// Messages of the replay stream, as recorded by a server for its first player
+    pub fn messages(&self) -> Vec<ServerMessage> {
+        let tile_map = &self.layout.tile_map;
+        let mut messages = vec![ServerMessage::Welcome(Welcome {
+            player: 0,
+            name: self.name.clone(),
+            mode: NetMode::Coop,
+            round: 0,
+            map_size: (tile_map.width(), tile_map.height()),
+            bomb_count: tile_map.bomb_count(),
+        })];
+        let mut board = ServerBoard::new(self.layout.clone().into_board());
+        messages.extend(board.snapshot());
+        for timing in self.moves.iter() {
+            let moved = match timing.mark {
+                true => board.mark(0, timing.coordinates, false, Some(timing.millis)),
+                false => board.trigger(0, timing.coordinates, Some(timing.millis)),
+            };
+            messages.extend(moved);
+        }
+        messages
+    }
+}
@@ -0,0 +1,727 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bevy::log;
+use rand::{thread_rng, Rng};
+
+use crate::components::Coordinates;
+use crate::net::protocol::*;
+use crate::resources::board::Board;
+use crate::resources::tile_map::TileMap;
+use crate::resources::GameStatus;
+
+/// Game server settings
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    pub mode: NetMode,
+    pub map_size: (u16, u16),
+    pub bomb_count: u16,
+    /// Does each board start with an uncovered opening
+    pub safe_start: bool,
+    /// Does marking cycle through a question mark after the flag
+    pub question_marks: bool,
+    /// Seed of the first round, random when `None`
+    pub seed: Option<u64>,
+    /// File recording what a spectator of the first player receives, to be tailed
+    /// by spectators without a connection
+    pub replay: Option<PathBuf>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            mode: Default::default(),
+            map_size: (16, 16),
+            bomb_count: 40,
+            safe_start: true,
+            question_marks: false,
+            seed: None,
+            replay: None,
+        }
+    }
+}
+
+/// Connection events, forwarded by the network threads to the game loop
+enum ServerEvent {
+    Connected(usize, TcpStream),
+    Message(usize, ClientMessage),
+    Disconnected(usize),
+}
+
+/// Client id of the replay file
+const REPLAY: usize = usize::MAX;
+
+/// Player watched by a spectator
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Watching {
+    /// Scoreboard index of a player yet to join
+    Index(usize),
+    /// Client id of the player, so that the spectator keeps watching them when the
+    /// scoreboard changes
+    Player(usize),
+}
+
+struct Client {
+    stream: Box<dyn Write + Send>,
+    /// Name and board index, once joined
+    player: Option<(String, usize)>,
+    /// Watched player, for spectators
+    watching: Option<Watching>,
+}
+
+/// Board played on the server, turning moves into the messages of its players
+pub(crate) struct ServerBoard {
+    pub(crate) board: Board,
+    pub(crate) status: GameStatus,
+    started: Option<Instant>,
+    /// Duration of the ended game
+    ended: Option<Duration>,
+}
+
+impl ServerBoard {
+    pub(crate) fn new(board: Board) -> Self {
+        Self {
+            board,
+            status: GameStatus::Playing,
+            started: None,
+            ended: None,
+        }
+    }
+
+    fn seconds(&self) -> u64 {
+        match (self.ended, self.started) {
+            (Some(duration), _) => duration.as_secs(),
+            (None, Some(started)) => started.elapsed().as_secs(),
+            (None, None) => 0,
+        }
+    }
+
+    fn end(&mut self, status: GameStatus) {
+        self.status = status;
+        self.ended = Some(self.started.map_or(Duration::ZERO, |s| s.elapsed()));
+    }
+
+    /// Milliseconds since the first uncovered tiles
+    fn millis(&self) -> u64 {
+        self.started.map_or(0, |s| s.elapsed().as_millis() as u64)
+    }
+
+    /// Messages sending the board as played so far
+    pub(crate) fn snapshot(&self) -> Vec<ServerMessage> {
+        let mut messages = vec![ServerMessage::Uncovered {
+            tiles: self.board.uncovered_tiles(),
+            origin: None,
+            chord: false,
+        }];
+        messages.extend(self.board.marks().into_iter().map(|(coordinates, mark)| {
+            ServerMessage::Marked {
+                coordinates,
+                mark: Some(mark),
+            }
+        }));
+        if self.status == GameStatus::Lost {
+            messages.push(ServerMessage::Exploded {
+                coordinates: self
+                    .board
+                    .bombs()
+                    .into_iter()
+                    .find(|c| !self.board.covered_tiles.contains(c))
+                    .unwrap_or_default(),
+                bombs: self.board.bombs(),
+            });
+        }
+        messages
+    }
+
+    /// Uncovers or chords a tile, as `trigger_event_handler` does. `millis` is the time
+    /// of a recorded move, measured when `None`. Returns no message if nothing changed
+    pub(crate) fn trigger(
+        &mut self,
+        player: usize,
+        coordinates: Coordinates,
+        millis: Option<u64>,
+    ) -> Vec<ServerMessage> {
+        if self.status != GameStatus::Playing || self.board.marked_tiles.contains(&coordinates) {
+            return Vec::new();
+        }
+        let chord = !self.board.covered_tiles.contains(&coordinates);
+        let opening = match chord {
+            true => self.board.chord(coordinates),
+            false => self.board.reveal(coordinates),
+        };
+        if opening.is_empty() {
+            return Vec::new();
+        }
+        self.started.get_or_insert_with(Instant::now);
+        let tiles: Vec<_> = opening
+            .iter()
+            .filter_map(|(c, _)| Some((*c, *self.board.tile_map.tile_at(*c)?)))
+            .collect();
+        let bomb = tiles.iter().find(|(_, tile)| tile.is_bomb()).map(|(c, _)| *c);
+        let mut messages = vec![
+            ServerMessage::Moved(MoveTiming {
+                player,
+                coordinates,
+                mark: false,
+                millis: millis.unwrap_or_else(|| self.millis()),
+            }),
+            ServerMessage::Uncovered {
+                tiles,
+                origin: Some(coordinates),
+                chord,
+            },
+        ];
+        if let Some(bomb) = bomb {
+            self.end(GameStatus::Lost);
+            messages.push(ServerMessage::Exploded {
+                coordinates: bomb,
+                bombs: self.board.bombs(),
+            });
+        } else if self.board.is_completed() {
+            self.end(GameStatus::Won);
+            messages.push(ServerMessage::Completed);
+        }
+        messages
+    }
+
+    /// Toggles the mark of a tile, returning no message if nothing changed
+    pub(crate) fn mark(
+        &mut self,
+        player: usize,
+        coordinates: Coordinates,
+        question_marks: bool,
+        millis: Option<u64>,
+    ) -> Vec<ServerMessage> {
+        if self.status != GameStatus::Playing {
+            return Vec::new();
+        }
+        let Some(mark) = self.board.toggle_mark(&coordinates, question_marks) else {
+            return Vec::new();
+        };
+        vec![
+            ServerMessage::Moved(MoveTiming {
+                player,
+                coordinates,
+                mark: true,
+                millis: millis.unwrap_or_else(|| self.millis()),
+            }),
+            ServerMessage::Marked { coordinates, mark },
+        ]
+    }
+}
+
+/// Authoritative game server. Boards are only played here, clients send their moves
+/// and receive the uncovered tiles
+pub struct GameServer {
+    options: ServerOptions,
+    seed: u64,
+    round: u32,
+    boards: Vec<ServerBoard>,
+    clients: BTreeMap<usize, Client>,
+}
+
+impl GameServer {
+    pub fn new(options: ServerOptions) -> Self {
+        let seed = options.seed.unwrap_or_else(|| thread_rng().gen());
+        let mut server = Self {
+            options,
+            seed,
+            round: 0,
+            boards: Vec::new(),
+            clients: BTreeMap::new(),
+        };
+        if server.options.mode == NetMode::Coop {
+            server.boards.push(server.new_board());
+        }
+        server
+    }
+
+    /// Listens on `address` and serves the clients, until the listener fails
+    pub fn run(self, address: impl ToSocketAddrs) -> io::Result<()> {
+        self.serve(TcpListener::bind(address)?)
+    }
+
+    /// Serves the clients of a bound listener, until it fails
+    pub fn serve(mut self, listener: TcpListener) -> io::Result<()> {
+        if let Some(path) = &self.options.replay {
+            log::info!("Recording replay to {}", path.display());
+            let client = Client {
+                stream: Box::new(File::create(path)?),
+                player: None,
+                watching: Some(Watching::Index(0)),
+            };
+            self.clients.insert(REPLAY, client);
+            self.welcome(REPLAY);
+        }
+        log::info!("Server listening on {} in {:?} mode", listener.local_addr()?, self.options.mode);
+        let (event_tx, event_rx) = mpsc::channel();
+        let accept_thread = thread::spawn(move || accept_clients(listener, event_tx));
+        for event in event_rx {
+            match event {
+                ServerEvent::Connected(id, stream) => {
+                    log::info!("Client {} connected", id);
+                    let client = Client {
+                        stream: Box::new(stream),
+                        player: None,
+                        watching: None,
+                    };
+                    self.clients.insert(id, client);
+                }
+                ServerEvent::Message(id, message) => self.handle_message(id, message),
+                ServerEvent::Disconnected(id) => {
+                    log::info!("Client {} disconnected", id);
+                    self.clients.remove(&id);
+                    self.broadcast_players();
+                }
+            }
+        }
+        accept_thread.join().unwrap_or_else(|_| Err(io::Error::other("accept thread panicked")))
+    }
+
+    fn new_board(&self) -> ServerBoard {
+        let (width, height) = self.options.map_size;
+        let mut tile_map = TileMap::empty(width, height);
+        tile_map.set_bombs(self.options.bomb_count, self.seed);
+        let safe_start = tile_map.safe_start();
+        let mut board = Board::headless(tile_map);
+        if let (true, Some(coordinates)) = (self.options.safe_start, safe_start) {
+            board.uncover_safe_start(coordinates);
+        }
+        ServerBoard::new(board)
+    }
+
+    /// Ids of the joined players, in scoreboard order
+    fn player_ids(&self) -> Vec<usize> {
+        self.clients
+            .iter()
+            .filter(|(_, c)| c.player.is_some())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Board index of a player, or of the player watched by a spectator
+    fn board_of(&self, id: usize) -> Option<usize> {
+        let client = self.clients.get(&id)?;
+        if let Some((_, board)) = &client.player {
+            return Some(*board);
+        }
+        let watching = client.watching?;
+        match (self.options.mode, watching) {
+            (NetMode::Coop, _) => Some(0),
+            (NetMode::Race, Watching::Index(_)) => None,
+            (NetMode::Race, Watching::Player(player)) => self.board_of(player),
+        }
+    }
+
+    fn handle_message(&mut self, id: usize, message: ClientMessage) {
+        log::debug!("Client {}: {:?}", id, message);
+        let Some(client) = self.clients.get(&id) else {
+            return;
+        };
+        let joined = client.player.is_some() || client.watching.is_some();
+        let board = client.player.as_ref().map(|(_, b)| *b);
+        match (message, board) {
+            (ClientMessage::Join { name }, None) if !joined => self.join(id, name),
+            (ClientMessage::Spectate { player }, None) if !joined => self.spectate(id, player),
+            (ClientMessage::Trigger(coordinates), Some(board)) => self.trigger(id, board, coordinates),
+            (ClientMessage::Mark(coordinates), Some(board)) => self.mark(id, board, coordinates),
+            (ClientMessage::Cursor(coordinates), Some(board)) => {
+                let player = self.player_ids().iter().position(|p| *p == id).unwrap_or_default();
+                self.send_board(board, &ServerMessage::Cursor { player, coordinates });
+            }
+            (ClientMessage::NewGame, Some(_)) => self.new_round(),
+            (message, _) => self.send(id, &ServerMessage::Error(format!("Unexpected {:?}", message))),
+        }
+    }
+
+    fn spectate(&mut self, id: usize, player: usize) {
+        log::info!("Client {} watching player {}", id, player);
+        let watching = match self.player_ids().get(player) {
+            Some(player) => Watching::Player(*player),
+            None => Watching::Index(player),
+        };
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.watching = Some(watching);
+        }
+        self.welcome(id);
+        self.broadcast_players();
+    }
+
+    fn join(&mut self, id: usize, name: String) {
+        let board = match self.options.mode {
+            NetMode::Coop => 0,
+            NetMode::Race => {
+                self.boards.push(self.new_board());
+                self.boards.len() - 1
+            }
+        };
+        log::info!("{} joined on board {}", name, board);
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.player = Some((name, board));
+        }
+        self.welcome(id);
+        // Spectators waiting for this player watch them from now on. In a race, they
+        // had no board to watch yet
+        let index = self.player_ids().iter().position(|p| *p == id).unwrap_or_default();
+        let spectators: Vec<usize> = self
+            .clients
+            .iter()
+            .filter(|(_, c)| c.watching == Some(Watching::Index(index)))
+            .map(|(id, _)| *id)
+            .collect();
+        for spectator in spectators {
+            if let Some(client) = self.clients.get_mut(&spectator) {
+                client.watching = Some(Watching::Player(id));
+            }
+            if self.options.mode == NetMode::Race {
+                self.welcome(spectator);
+            }
+        }
+        self.broadcast_players();
+    }
+
+    /// Starts the round of a player or spectator, sending the board as uncovered so far
+    fn welcome(&mut self, id: usize) {
+        let Some(board) = self.board_of(id) else {
+            return;
+        };
+        let player_ids = self.player_ids();
+        let index_of = |id: usize| player_ids.iter().position(|p| *p == id).unwrap_or_default();
+        let player = match self.clients.get(&id).and_then(|c| c.watching) {
+            Some(Watching::Index(index)) => index,
+            Some(Watching::Player(player)) => index_of(player),
+            None => index_of(id),
+        };
+        let name = player_ids
+            .get(player)
+            .and_then(|p| self.clients.get(p)?.player.as_ref())
+            .map_or_else(String::new, |(name, _)| name.clone());
+        self.send(id, &ServerMessage::Welcome(Welcome {
+            player,
+            name,
+            mode: self.options.mode,
+            round: self.round,
+            map_size: self.options.map_size,
+            bomb_count: self.options.bomb_count,
+        }));
+        for message in self.boards[board].snapshot() {
+            self.send(id, &message);
+        }
+    }
+
+    fn trigger(&mut self, id: usize, board: usize, coordinates: Coordinates) {
+        let player = self.player_ids().iter().position(|p| *p == id).unwrap_or_default();
+        let messages = self.boards[board].trigger(player, coordinates, None);
+        if messages.is_empty() {
+            return;
+        }
+        for message in messages {
+            self.send_board(board, &message);
+        }
+        self.broadcast_players();
+    }
+
+    fn mark(&mut self, id: usize, board: usize, coordinates: Coordinates) {
+        let player = self.player_ids().iter().position(|p| *p == id).unwrap_or_default();
+        let question_marks = self.options.question_marks;
+        for message in self.boards[board].mark(player, coordinates, question_marks, None) {
+            self.send_board(board, &message);
+        }
+    }
+
+    /// Is the round over: the shared board ended, or a race was won or lost by everyone
+    fn is_round_over(&self) -> bool {
+        let mut statuses = self.boards.iter().map(|b| b.status);
+        match self.options.mode {
+            NetMode::Coop => statuses.all(|s| s != GameStatus::Playing),
+            NetMode::Race => {
+                let statuses: Vec<_> = statuses.collect();
+                statuses.contains(&GameStatus::Won) || !statuses.contains(&GameStatus::Playing)
+            }
+        }
+    }
+
+    /// Generates new boards from a new seed for every player, once the round is over
+    fn new_round(&mut self) {
+        if !self.is_round_over() {
+            return;
+        }
+        self.seed = thread_rng().gen();
+        self.round += 1;
+        log::info!("Round {} starting", self.round);
+        self.boards.clear();
+        if self.options.mode == NetMode::Coop {
+            self.boards.push(self.new_board());
+        }
+        for id in self.player_ids() {
+            if self.options.mode == NetMode::Race {
+                self.boards.push(self.new_board());
+                let board = self.boards.len() - 1;
+                if let Some((_, b)) = self.clients.get_mut(&id).and_then(|c| c.player.as_mut()) {
+                    *b = board;
+                }
+            }
+        }
+        let ids: Vec<usize> = self.clients.keys().copied().collect();
+        for id in ids {
+            self.welcome(id);
+        }
+        self.broadcast_players();
+    }
+
+    fn broadcast_players(&mut self) {
+        let players: Vec<PlayerInfo> = self
+            .clients
+            .values()
+            .filter_map(|c| c.player.as_ref())
+            .map(|(name, board)| {
+                let server_board = &self.boards[*board];
+                let covered = server_board.board.covered_tiles.len();
+                PlayerInfo {
+                    name: name.clone(),
+                    status: server_board.status,
+                    tiles_left: covered.saturating_sub(server_board.board.tile_map.bomb_count() as usize),
+                    seconds: server_board.seconds(),
+                }
+            })
+            .collect();
+        let ids: Vec<usize> = self.clients.keys().copied().collect();
+        for id in ids {
+            self.send(id, &ServerMessage::Players(players.clone()));
+        }
+    }
+
+    /// Sends a message to the players and spectators of a board
+    fn send_board(&mut self, board: usize, message: &ServerMessage) {
+        let ids: Vec<usize> = self
+            .clients
+            .keys()
+            .copied()
+            .filter(|id| self.board_of(*id) == Some(board))
+            .collect();
+        for id in ids {
+            self.send(id, message);
+        }
+    }
+
+    /// Sends a message to a client, dropping it if the connection failed
+    fn send(&mut self, id: usize, message: &ServerMessage) {
+        let Some(client) = self.clients.get_mut(&id) else {
+            return;
+        };
+        if let Err(e) = write_message(&mut client.stream, message) {
+            log::warn!("Dropping client {}: {}", id, e);
+            self.clients.remove(&id);
+        }
+    }
+}
+
+/// Accepts the connections, reading each client on its own thread
+fn accept_clients(listener: TcpListener, event_tx: Sender<ServerEvent>) -> io::Result<()> {
+    for (id, stream) in listener.incoming().enumerate() {
+        let stream = stream?;
+        let reader = stream.try_clone()?;
+        if event_tx.send(ServerEvent::Connected(id, stream)).is_err() {
+            break;
+        }
+        let event_tx = event_tx.clone();
+        thread::spawn(move || {
+            for message in read_messages::<ClientMessage>(reader) {
+                match message {
+                    Ok(message) => {
+                        if event_tx.send(ServerEvent::Message(id, message)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Client {}: {}", id, e);
+                        break;
+                    }
+                }
+            }
+            let _ = event_tx.send(ServerEvent::Disconnected(id));
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::NetClient;
+    use crate::resources::BoardOptions;
+    use std::net::SocketAddr;
+
+    const SEED: u64 = 42;
+
+    /// Starts a server on a free local port
+    fn start(mode: NetMode) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = GameServer::new(ServerOptions {
+            mode,
+            seed: Some(SEED),
+            ..Default::default()
+        });
+        thread::spawn(move || server.serve(listener));
+        address
+    }
+
+    /// Board each player starts the first round with
+    fn local_board() -> Board {
+        let options = ServerOptions::default();
+        let options = BoardOptions {
+            map_size: options.map_size,
+            bomb_count: options.bomb_count,
+            safe_start: options.safe_start,
+            ..Default::default()
+        };
+        Board::random(&options, SEED)
+    }
+
+    /// Receives messages until `done`, failing after a few seconds
+    fn wait(client: &mut NetClient, done: impl Fn(&NetClient) -> bool) {
+        let started = Instant::now();
+        loop {
+            client.receive();
+            if done(client) {
+                return;
+            }
+            assert!(started.elapsed() < Duration::from_secs(5), "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn players(count: usize) -> impl Fn(&NetClient) -> bool {
+        move |client| client.welcome.is_some() && client.players.len() == count
+    }
+
+    fn exploded(client: &NetClient) -> bool {
+        client.pending.iter().any(|m| matches!(m, ServerMessage::Exploded { .. }))
+    }
+
+    fn uncovered(client: &NetClient) -> Vec<Coordinates> {
+        client
+            .pending
+            .iter()
+            .filter_map(|m| match m {
+                ServerMessage::Uncovered { tiles, .. } => Some(tiles.iter().map(|(c, _)| *c)),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Checks that the only bomb sent outside `Exploded` is the one triggered by the
+    /// losing move, right before the explosion
+    fn assert_bombs_hidden(client: &NetClient, bombs: &[Coordinates]) {
+        let messages: Vec<_> = client.pending.iter().collect();
+        for (index, message) in messages.iter().enumerate() {
+            match message {
+                ServerMessage::Uncovered { tiles, origin, .. } => {
+                    for (coordinates, tile) in tiles {
+                        if tile.is_bomb() {
+                            assert_eq!(Some(*coordinates), *origin);
+                            assert!(matches!(messages.get(index + 1), Some(ServerMessage::Exploded { .. })));
+                        }
+                    }
+                }
+                ServerMessage::Exploded { bombs: sent, .. } => {
+                    let mut sent = sent.clone();
+                    sent.sort();
+                    assert_eq!(sent, bombs);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn coop() {
+        let address = start(NetMode::Coop);
+        let board = local_board();
+        let mut bombs = board.bombs();
+        bombs.sort();
+        let safe = board
+            .coordinates()
+            .find(|c| board.covered_tiles.contains(c) && !board.tile_map.is_bomb_at(*c))
+            .unwrap();
+        let mut ann = NetClient::connect(address, "Ann").unwrap();
+        wait(&mut ann, players(1));
+        let mut bob = NetClient::connect(address, "Bob").unwrap();
+        wait(&mut bob, players(2));
+        // Both play the shared board
+        ann.send(&ClientMessage::Trigger(safe));
+        wait(&mut ann, |c| uncovered(c).contains(&safe));
+        wait(&mut bob, |c| uncovered(c).contains(&safe));
+        assert!(ann.pending.iter().chain(bob.pending.iter()).all(|m| match m {
+            ServerMessage::Uncovered { tiles, .. } => tiles.iter().all(|(_, t)| !t.is_bomb()),
+            _ => !matches!(m, ServerMessage::Exploded { .. }),
+        }));
+        bob.send(&ClientMessage::Trigger(bombs[0]));
+        wait(&mut ann, exploded);
+        wait(&mut bob, exploded);
+        assert_bombs_hidden(&ann, &bombs);
+        assert_bombs_hidden(&bob, &bombs);
+    }
+
+    fn marked(client: &NetClient, coordinates: Coordinates) -> usize {
+        client
+            .pending
+            .iter()
+            .filter(|m| matches!(m, ServerMessage::Marked { coordinates: c, .. } if *c == coordinates))
+            .count()
+    }
+
+    #[test]
+    fn race() {
+        let address = start(NetMode::Race);
+        let board = local_board();
+        let mut bombs = board.bombs();
+        bombs.sort();
+        let mut safe = board
+            .coordinates()
+            .filter(|c| board.covered_tiles.contains(c) && !board.tile_map.is_bomb_at(*c));
+        let (first, second) = (safe.next().unwrap(), safe.last().unwrap());
+        let mut ann = NetClient::connect(address, "Ann").unwrap();
+        wait(&mut ann, players(1));
+        let mut bob = NetClient::connect(address, "Bob").unwrap();
+        wait(&mut bob, players(2));
+        let mut cid = NetClient::connect(address, "Cid").unwrap();
+        wait(&mut cid, players(3));
+        let mut spectator = NetClient::spectate(address, 1).unwrap();
+        wait(&mut spectator, |c| c.welcome.as_ref().is_some_and(|w| w.name == "Bob"));
+        // Each player only receives their own board
+        ann.send(&ClientMessage::Trigger(first));
+        wait(&mut ann, |c| uncovered(c).contains(&first));
+        assert_bombs_hidden(&ann, &bombs);
+        // The spectator keeps watching Bob once Ann, before him on the scoreboard, leaves
+        drop(ann);
+        wait(&mut bob, players(2));
+        wait(&mut spectator, |c| c.players.len() == 2);
+        cid.send(&ClientMessage::Mark(second));
+        wait(&mut cid, |c| marked(c, second) == 1);
+        bob.send(&ClientMessage::Mark(second));
+        wait(&mut bob, |c| marked(c, second) == 1);
+        wait(&mut spectator, |c| marked(c, second) == 1);
+        // Bob's explosion isn't sent to Cid, whose next move comes after it
+        bob.send(&ClientMessage::Trigger(bombs[0]));
+        wait(&mut bob, exploded);
+        wait(&mut spectator, exploded);
+        cid.send(&ClientMessage::Mark(second));
+        wait(&mut cid, |c| marked(c, second) == 2);
+        assert!(!exploded(&cid));
+        assert!(!uncovered(&bob).contains(&first));
+        assert!(!uncovered(&cid).contains(&first));
+        assert_eq!(marked(&spectator, second), 1);
+        assert_bombs_hidden(&bob, &bombs);
+        assert_bombs_hidden(&cid, &bombs);
+        assert_bombs_hidden(&spectator, &bombs);
+    }
+}
@@ -1,25 +1,70 @@
 use crate::bounds::Bounds2;
+use crate::resources::move_history::{Move, MoveHistory};
+use crate::resources::tile::{Mark, Tile};
+use crate::resources::{BoardOptions, GameMode};
+use crate::resources::tile_bitset::TileBitSet;
 use crate::{Coordinates, TileMap};
 use bevy::{log, prelude::*};
 use bevy::utils::HashMap;
+use std::collections::VecDeque;
 
-#[derive(Debug, Resource)]
+/// Side of a square chunk of tiles, in tiles
+pub const CHUNK_SIZE: i32 = 16;
+
+/// Board state, on the board entity
+#[derive(Debug, Component)]
 pub struct Board {
     pub tile_map: TileMap,
     pub bounds: Bounds2,
     pub tile_size:  f32,
-    pub covered_tiles: HashMap<Coordinates, Entity>,
+    pub covered_tiles: TileBitSet,
+    /// Spawned chunk entities, by chunk coordinates
+    pub chunks: HashMap<Coordinates, Entity>,
     pub entity: Entity,
-    pub marked_tiles: Vec<Coordinates>,
+    pub marked_tiles: TileBitSet,
+    /// Question marked tiles, which don't count as marked
+    pub question_tiles: TileBitSet,
+    pub history: MoveHistory,
+    /// First uncovered bomb
+    exploded: Option<Coordinates>,
 }
 
 impl Board {
-    /// Translates a mouse position to board coordinates
-    pub fn mouse_position(&self, window: &Window, position: Vec2) -> Option<Coordinates> {
-        // Window to world space
-        let window_size = Vec2::new(window.width(), window.height());
-        let position = position - window_size / 2.;
+    /// Generates a board without entities, as played by a server
+    pub fn headless(tile_map: TileMap) -> Self {
+        let (width, height) = (tile_map.width(), tile_map.height());
+        Self {
+            covered_tiles: TileBitSet::full(width, height),
+            tile_map,
+            bounds: Bounds2 {
+                position: Vec2::ZERO,
+                size: Vec2::ZERO,
+            },
+            tile_size: 0.,
+            chunks: HashMap::new(),
+            entity: Entity::PLACEHOLDER,
+            marked_tiles: TileBitSet::empty(width, height),
+            question_tiles: TileBitSet::empty(width, height),
+            history: MoveHistory::default(),
+            exploded: None,
+        }
+    }
 
+    /// Generates a random headless board from a seed, its safe start uncovered if enabled.
+    /// Chunks are spawned afterwards, so the opening is drawn uncovered from the start
+    pub fn random(options: &BoardOptions, seed: u64) -> Self {
+        let mut tile_map = TileMap::empty(options.map_size.0, options.map_size.1);
+        tile_map.set_bombs(options.bomb_count, seed);
+        let safe_start = tile_map.safe_start();
+        let mut board = Self::headless(tile_map);
+        if let (true, Some(coordinates)) = (options.safe_start, safe_start) {
+            board.uncover_safe_start(coordinates);
+        }
+        board
+    }
+
+    /// Translates a mouse world position to board coordinates
+    pub fn mouse_position(&self, position: Vec2) -> Option<Coordinates> {
         // Bounds check
         if !self.bounds.in_bounds(position) {
             return None;
@@ -27,61 +72,385 @@ impl Board {
         // World space to board space
         let coordinates = position - self.bounds.position;
         Some(Coordinates {
-            x: (coordinates.x / self.tile_size) as u16,
-            y: (coordinates.y / self.tile_size) as u16,
+            x: ((coordinates.x / self.tile_size) as i32).min(self.tile_map.width() as i32 - 1),
+            y: ((coordinates.y / self.tile_size) as i32).min(self.tile_map.height() as i32 - 1),
         })
     }
 
-    /// Retrieves a covered tile entity
-    pub fn tile_to_uncover(&self, coords: &Coordinates) -> Option<&Entity> {
-        if self.marked_tiles.contains(coords) {
-            None
-        } else {
-            self.covered_tiles.get(coords)
+    /// Uncovers a covered tile and its opening, recording the move.
+    /// Returns the uncovered tiles along with their distance to `coords`, in rings
+    pub fn reveal(&mut self, coords: Coordinates) -> Vec<(Coordinates, u32)> {
+        let mut unmarked = Vec::new();
+        let opening = self.uncover_opening(coords, &mut unmarked);
+        if !opening.is_empty() {
+            self.history.push(Move::Reveal {
+                origin: coords,
+                tiles: opening.iter().map(|(c, _)| *c).collect(),
+                unmarked,
+            });
         }
+        opening
     }
 
-    /// We try to uncover a tile, returning the entity
-    pub fn try_uncover_tile(&mut self, coords: &Coordinates) -> Option<Entity> {
-        if self.marked_tiles.contains(coords) {
-            self.unmark_tile(coords)?;
+    /// Uncovers the unmarked neighbors of an uncovered bomb neighbor tile, and
+    /// their openings, if it has as many marked neighbors as neighbor bombs.
+    /// Returns the uncovered tiles along with their distance to the neighbor they
+    /// were uncovered from, in rings
+    pub fn chord(&mut self, coords: Coordinates) -> Vec<(Coordinates, u32)> {
+        if self.covered_tiles.contains(&coords) {
+            return Vec::new();
         }
-        self.covered_tiles.remove(coords)
+        let Some(Tile::BombNeighbor(count)) = self.tile_map.tile_at(coords).copied() else {
+            return Vec::new();
+        };
+        let neighbors: Vec<Coordinates> = self.tile_map.safe_square_at(coords).collect();
+        let marked = neighbors.iter().filter(|c| self.marked_tiles.contains(c)).count();
+        if marked != count as usize {
+            return Vec::new();
+        }
+        let mut unmarked = Vec::new();
+        let mut opening = Vec::new();
+        for neighbor in neighbors {
+            if !self.marked_tiles.contains(&neighbor) {
+                opening.extend(self.uncover_opening(neighbor, &mut unmarked));
+            }
+        }
+        if !opening.is_empty() {
+            self.history.push(Move::Chord {
+                origin: coords,
+                tiles: opening.iter().map(|(c, _)| *c).collect(),
+                unmarked,
+            });
+        }
+        opening
     }
 
-    /// We retrieve the adjacent coverd tile entities of 'coord'
-    pub fn adjacent_covered_tiles(&self, coord: Coordinates) -> Vec<Entity> {
-        self.tile_map
-            .safe_square_at(coord)
-            .filter_map(|c| self.covered_tiles.get(&c))
-            .copied()
-            .collect()
+    /// Uncovers the safe start opening, which can't be undone
+    pub fn uncover_safe_start(&mut self, coords: Coordinates) {
+        self.uncover_opening(coords, &mut Vec::new());
     }
 
-    fn unmark_tile(&mut self, coords: &Coordinates) -> Option<Coordinates> {
-        let pos = match self.marked_tiles.iter().position(|a| a == coords) {
-            None => {
-                log::error!("Failed to unmark tile at {}", coords);
-                return None;
+    /// Uncovers a tile and, if it is empty, the whole opening around it.
+    /// Returns the uncovered tiles along with their distance to `coords`, in rings
+    fn uncover_opening(
+        &mut self,
+        coords: Coordinates,
+        unmarked: &mut Vec<(Coordinates, Mark)>,
+    ) -> Vec<(Coordinates, u32)> {
+        let mut uncovered = Vec::new();
+        let mut queue = VecDeque::new();
+        if self.try_uncover_tile(&coords, unmarked) {
+            queue.push_back((coords, 0));
+        }
+        while let Some((coords, ring)) = queue.pop_front() {
+            uncovered.push((coords, ring));
+            if let Some(Tile::Empty) = self.tile_map.tile_at(coords) {
+                let neighbors: Vec<Coordinates> = self.tile_map.safe_square_at(coords).collect();
+                for neighbor in neighbors {
+                    if self.try_uncover_tile(&neighbor, unmarked) {
+                        queue.push_back((neighbor, ring + 1));
+                    }
+                }
             }
-            Some(p) => p,
+        }
+        uncovered
+    }
+
+    /// We try to uncover a tile, removing its mark
+    fn try_uncover_tile(
+        &mut self,
+        coords: &Coordinates,
+        unmarked: &mut Vec<(Coordinates, Mark)>,
+    ) -> bool {
+        if !self.uncover_tile(coords) {
+            return false;
+        }
+        if let Some(mark) = self.mark_at(coords) {
+            self.set_mark(coords, None);
+            unmarked.push((*coords, mark));
+        }
+        true
+    }
+
+    /// Uncovers a single tile, remembering the first uncovered bomb.
+    /// Returns `false` if it was not covered
+    pub(crate) fn uncover_tile(&mut self, coords: &Coordinates) -> bool {
+        if !self.covered_tiles.remove(coords) {
+            return false;
+        }
+        if self.exploded.is_none() && self.tile_map.is_bomb_at(*coords) {
+            self.exploded = Some(*coords);
+        }
+        true
+    }
+
+    /// Reverts the last move. Moves uncovering a bomb can only be reverted
+    /// if `mode` allows it
+    pub fn undo(&mut self, mode: GameMode) -> Option<Move> {
+        let last = self.history.last()?;
+        let uncovers_bomb = matches!(last, Move::Reveal { .. } | Move::Chord { .. })
+            && last.tiles().iter().any(|c| self.tile_map.is_bomb_at(*c));
+        if uncovers_bomb && !mode.allows_bomb_undo() {
+            log::info!("Bomb moves can't be undone in {:?} mode", mode);
+            return None;
+        }
+        let m = self.history.undo()?;
+        match &m {
+            Move::Reveal { tiles, unmarked, .. } | Move::Chord { tiles, unmarked, .. } => {
+                for c in tiles {
+                    self.covered_tiles.insert(c);
+                }
+                if self.exploded.is_some_and(|e| tiles.contains(&e)) {
+                    self.exploded = None;
+                }
+                for (c, mark) in unmarked {
+                    self.set_mark(c, Some(*mark));
+                }
+            }
+            Move::Mark { tile, from, .. } => self.set_mark(tile, *from),
+        }
+        Some(m)
+    }
+
+    /// Plays again the last undone move
+    pub fn redo(&mut self) -> Option<Move> {
+        let m = self.history.redo()?;
+        match &m {
+            Move::Reveal { tiles, .. } | Move::Chord { tiles, .. } => {
+                for c in tiles {
+                    self.try_uncover_tile(c, &mut Vec::new());
+                }
+            }
+            Move::Mark { tile, to, .. } => self.set_mark(tile, *to),
+        }
+        Some(m)
+    }
+
+    /// Retrieves the mark of a tile
+    pub fn mark_at(&self, coords: &Coordinates) -> Option<Mark> {
+        if self.marked_tiles.contains(coords) {
+            Some(Mark::Flag)
+        } else if self.question_tiles.contains(coords) {
+            Some(Mark::Question)
+        } else {
+            None
+        }
+    }
+
+    fn set_mark(&mut self, coords: &Coordinates, mark: Option<Mark>) {
+        self.marked_tiles.remove(coords);
+        self.question_tiles.remove(coords);
+        match mark {
+            Some(Mark::Flag) => self.marked_tiles.insert(coords),
+            Some(Mark::Question) => self.question_tiles.insert(coords),
+            None => false,
         };
-        Some(self.marked_tiles.remove(pos))
     }
 
     pub fn is_completed(&self) -> bool {
         self.tile_map.bomb_count() as usize == self.covered_tiles.len()
     }
 
-    pub fn try_toggle_mark(&mut self, coords: &Coordinates) -> Option<(Entity, bool)> {
-        let entity = *self.covered_tiles.get(coords)?;
-        let mark = if self.marked_tiles.contains(coords) {
-            self.unmark_tile(coords)?;
-            false
-        } else {
-            self.marked_tiles.push(*coords);
-            true
+    /// Was a bomb uncovered
+    pub fn is_exploded(&self) -> bool {
+        self.exploded.is_some()
+    }
+
+    /// Retrieves the coordinates of every tile, row by row
+    pub fn coordinates(&self) -> impl Iterator<Item = Coordinates> {
+        let (width, height) = (self.tile_map.width() as i32, self.tile_map.height() as i32);
+        (0..height).flat_map(move |y| (0..width).map(move |x| Coordinates { x, y }))
+    }
+
+    /// Retrieves the uncovered tiles with their content
+    pub fn uncovered_tiles(&self) -> Vec<(Coordinates, Tile)> {
+        self.coordinates()
+            .filter(|c| !self.covered_tiles.contains(c))
+            .filter_map(|c| Some((c, *self.tile_map.tile_at(c)?)))
+            .collect()
+    }
+
+    /// Retrieves the marked tiles with their mark
+    pub fn marks(&self) -> Vec<(Coordinates, Mark)> {
+        self.coordinates()
+            .filter_map(|c| Some((c, self.mark_at(&c)?)))
+            .collect()
+    }
+
+    /// Retrieves the bomb coordinates
+    pub fn bombs(&self) -> Vec<Coordinates> {
+        self.coordinates()
+            .filter(|c| self.tile_map.is_bomb_at(*c))
+            .collect()
+    }
+
+    /// Uncovers tiles of a remote board, learning their content from the server
+    pub fn apply_uncovered(&mut self, tiles: &[(Coordinates, Tile)]) {
+        for (coords, tile) in tiles {
+            if self.tile_map.tile_at(*coords).is_none() {
+                continue;
+            }
+            self.tile_map[coords.y as usize][coords.x as usize] = *tile;
+            self.uncover_tile(coords);
+            self.set_mark(coords, None);
+        }
+    }
+
+    /// Sets the mark of a tile of a remote board, as played on the server
+    pub fn apply_mark(&mut self, coords: &Coordinates, mark: Option<Mark>) {
+        self.set_mark(coords, mark);
+    }
+
+    /// Learns the bombs of a remote board, sent by the server once the game is lost
+    pub fn apply_bombs(&mut self, bombs: &[Coordinates]) {
+        for coords in bombs {
+            if self.tile_map.tile_at(*coords).is_some() {
+                self.tile_map[coords.y as usize][coords.x as usize] = Tile::Bomb;
+            }
+        }
+    }
+
+    /// Retrieves the covered and unflagged bombs with their distance to `origin`
+    /// in tiles, nearest first
+    pub fn hidden_bombs_by_distance(&self, origin: Coordinates) -> Vec<(Coordinates, f32)> {
+        let mut bombs: Vec<(Coordinates, f32)> = self
+            .coordinates()
+            .filter(|c| self.tile_map.is_bomb_at(*c) && self.covered_tiles.contains(c))
+            .filter(|c| self.mark_at(c) != Some(Mark::Flag))
+            .map(|c| {
+                let distance = Vec2::new((c.x - origin.x) as f32, (c.y - origin.y) as f32).length();
+                (c, distance)
+            })
+            .collect();
+        bombs.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        bombs
+    }
+
+    /// Cycles the mark of a covered tile through flag and, if `question_marks` is set,
+    /// question mark. Returns the new mark, or `None` if the tile isn't covered
+    pub fn toggle_mark(&mut self, coords: &Coordinates, question_marks: bool) -> Option<Option<Mark>> {
+        if !self.covered_tiles.contains(coords) {
+            return None;
+        }
+        let from = self.mark_at(coords);
+        let to = match from {
+            None => Some(Mark::Flag),
+            Some(Mark::Flag) if question_marks => Some(Mark::Question),
+            Some(Mark::Flag) | Some(Mark::Question) => None,
         };
-        Some((entity, mark))
+        self.set_mark(coords, to);
+        self.history.push(Move::Mark {
+            tile: *coords,
+            from,
+            to,
+        });
+        Some(to)
+    }
+
+    /// Retrieves the chunks overlapping the `min`..`max` world area
+    pub fn chunks_in_view(&self, min: Vec2, max: Vec2) -> Vec<Coordinates> {
+        let min = (min - self.bounds.position) / (self.tile_size * CHUNK_SIZE as f32);
+        let max = (max - self.bounds.position) / (self.tile_size * CHUNK_SIZE as f32);
+        let last_x = (self.tile_map.width() as i32 - 1) / CHUNK_SIZE;
+        let last_y = (self.tile_map.height() as i32 - 1) / CHUNK_SIZE;
+        let (min_x, max_x) = ((min.x.floor() as i32).max(0), (max.x.floor() as i32).min(last_x));
+        let (min_y, max_y) = ((min.y.floor() as i32).max(0), (max.y.floor() as i32).min(last_y));
+        (min_y..=max_y)
+            .flat_map(|y| (min_x..=max_x).map(move |x| Coordinates { x, y }))
+            .collect()
+    }
+
+    /// Retrieves the coordinates of the chunk holding `coords`
+    pub fn chunk_of(coords: Coordinates) -> Coordinates {
+        Coordinates {
+            x: coords.x / CHUNK_SIZE,
+            y: coords.y / CHUNK_SIZE,
+        }
+    }
+
+    /// Forgets a spawned chunk, returning the chunk entity to despawn.
+    /// The chunk is spawned again from the board state when in view
+    pub fn remove_chunk(&mut self, chunk: Coordinates) -> Option<Entity> {
+        self.chunks.remove(&chunk)
+    }
+
+    /// Retrieves the tile coordinates of a chunk
+    pub fn chunk_tiles(&self, chunk: Coordinates) -> impl Iterator<Item = Coordinates> {
+        let start_x = chunk.x * CHUNK_SIZE;
+        let start_y = chunk.y * CHUNK_SIZE;
+        let end_x = (start_x + CHUNK_SIZE).min(self.tile_map.width() as i32);
+        let end_y = (start_y + CHUNK_SIZE).min(self.tile_map.height() as i32);
+        (start_y..end_y).flat_map(move |y| (start_x..end_x).map(move |x| Coordinates { x, y }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::BoardLayout;
+
+    /// 3x3 board with its bomb in the top right corner
+    fn corner_bomb_board() -> Board {
+        "..*\n...\n...\n".parse::<BoardLayout>().unwrap().into_board()
+    }
+
+    #[test]
+    fn undoing_a_loss() {
+        let mut board = corner_bomb_board();
+        let bomb = Coordinates { x: 2, y: 2 };
+        board.reveal(bomb);
+        assert!(board.is_exploded());
+        assert!(board.undo(GameMode::Ranked).is_none());
+        assert!(board.is_exploded());
+
+        assert!(matches!(board.undo(GameMode::Practice), Some(Move::Reveal { .. })));
+        assert!(!board.is_exploded());
+        assert!(board.covered_tiles.contains(&bomb));
+        assert_eq!(board.covered_tiles.len(), 9);
+
+        board.redo();
+        assert!(board.is_exploded());
+        assert!(!board.covered_tiles.contains(&bomb));
+    }
+
+    #[test]
+    fn undoing_a_chord() {
+        let mut board = corner_bomb_board();
+        let (center, bomb) = (Coordinates { x: 1, y: 1 }, Coordinates { x: 2, y: 2 });
+        assert_eq!(board.reveal(center).len(), 1);
+        board.toggle_mark(&bomb, false);
+        assert_eq!(board.chord(center).len(), 7);
+        assert!(board.is_completed());
+
+        assert!(matches!(board.undo(GameMode::Ranked), Some(Move::Chord { .. })));
+        assert_eq!(board.covered_tiles.len(), 8);
+        assert!(!board.covered_tiles.contains(&center));
+        assert_eq!(board.mark_at(&bomb), Some(Mark::Flag));
+        assert!(!board.is_completed());
+
+        assert!(matches!(board.redo(), Some(Move::Chord { .. })));
+        assert!(board.is_completed());
+        assert!(!board.is_exploded());
+    }
+
+    #[test]
+    fn undoing_a_mark() {
+        let mut board = corner_bomb_board();
+        let tile = Coordinates { x: 0, y: 0 };
+        board.toggle_mark(&tile, true);
+        board.toggle_mark(&tile, true);
+        assert_eq!(board.mark_at(&tile), Some(Mark::Question));
+
+        board.undo(GameMode::Ranked);
+        assert_eq!(board.mark_at(&tile), Some(Mark::Flag));
+        board.redo();
+        assert_eq!(board.mark_at(&tile), Some(Mark::Question));
+
+        // Undoing the opening that uncovered the marked tile brings its mark back
+        board.reveal(Coordinates { x: 0, y: 1 });
+        assert_eq!(board.mark_at(&tile), None);
+        board.undo(GameMode::Ranked);
+        assert_eq!(board.mark_at(&tile), Some(Mark::Question));
+        assert!(board.covered_tiles.contains(&tile));
     }
 }
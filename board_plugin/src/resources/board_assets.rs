@@ -1,4 +1,8 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::SkinPart;
+use crate::resources::{BoardSounds, Palette};
 
 #[derive(Debug, Clone, Default)]
 pub struct SpriteMaterial {
@@ -15,36 +19,117 @@ impl SpriteMaterial {
     }
 }
 
+/// Indices of the tile states in a `TileAtlas` layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtlasIndices {
+    pub covered: usize,
+    /// Pressed tile, also used for uncovered empty tiles
+    pub pressed: usize,
+    /// Bomb neighbor tiles, from 1 bomb up to 8
+    pub numbers: [usize; 8],
+    pub flag: usize,
+    /// Question mark, drawn with `question_material` when missing
+    #[serde(default)]
+    pub question: Option<usize>,
+    pub mine: usize,
+    pub exploded: usize,
+    pub wrong_flag: usize,
+}
+
+/// Sprite sheet holding every tile state
+#[derive(Debug, Clone)]
+pub struct TileAtlas {
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub indices: AtlasIndices,
+}
+
+/// Sprite of a skin part
+#[derive(Debug, Clone)]
+pub struct SkinSprite {
+    pub texture: Handle<Image>,
+    pub color: Color,
+    pub atlas: Option<TextureAtlas>,
+}
+
 #[derive(Debug, Clone, Resource)]
 pub struct BoardAssets {
     /// Label
     pub label: String,
-    ///
+    /// Board background
     pub board_material: SpriteMaterial,
-    ///
+    /// Uncovered tile
     pub tile_material: SpriteMaterial,
-    ///
+    /// Tile cover
     pub covered_tile_material: SpriteMaterial,
-    ///
+    /// Bomb counter font
     pub bomb_counter_font: Handle<Font>,
-    ///
+    /// Bomb counter colors, from 1 bomb up
     pub bomb_counter_colors: Vec<Color>,
-    ///
+    /// Flag mark
     pub flag_material: SpriteMaterial,
-    ///
+    /// Question mark
+    pub question_material: SpriteMaterial,
+    /// Bomb
     pub bomb_material: SpriteMaterial,
+    /// Tile sprite sheet, replacing the tile materials and bomb counter texts
+    pub atlas: Option<TileAtlas>,
+    /// Sound effects
+    pub sounds: BoardSounds,
 }
 
 impl BoardAssets {
     /// Default bomb counter color set
     pub fn default_color() -> Vec<Color> {
-        vec![
-            Color::WHITE,
-            Color::srgba(0.0, 1.0, 0.0, 1.0),
-            Color::srgba(0.9, 0.9, 0.1, 1.0),
-            Color::srgba(1.0, 0.8, 0.6, 1.0),
-            Color::srgba(0.2, 0.1, 0.5, 1.0),
-        ]
+        Palette::Classic.colors().to_vec()
+    }
+
+    /// Retrieves the sprite of a skin part, `None` for bomb counters drawn as text
+    pub fn skin_sprite(&self, part: SkinPart) -> Option<SkinSprite> {
+        if let Some(atlas) = &self.atlas {
+            let indices = &atlas.indices;
+            let index = match part {
+                SkinPart::Background => None,
+                SkinPart::Tile => Some(indices.pressed),
+                SkinPart::Cover => Some(indices.covered),
+                SkinPart::Flag => Some(indices.flag),
+                SkinPart::WrongFlag => Some(indices.wrong_flag),
+                SkinPart::Question => indices.question,
+                SkinPart::Bomb => Some(indices.mine),
+                SkinPart::ExplodedBomb => Some(indices.exploded),
+                SkinPart::BombCounter(count) => {
+                    Some(indices.numbers[(count.clamp(1, 8) - 1) as usize])
+                }
+            };
+            if let Some(index) = index {
+                return Some(SkinSprite {
+                    texture: atlas.texture.clone(),
+                    color: Color::WHITE,
+                    atlas: Some(TextureAtlas {
+                        layout: atlas.layout.clone(),
+                        index,
+                    }),
+                });
+            }
+        }
+        let (material, color) = match part {
+            SkinPart::Background => (&self.board_material, self.board_material.color),
+            SkinPart::Tile => (&self.tile_material, self.tile_material.color),
+            SkinPart::Cover => (&self.covered_tile_material, self.covered_tile_material.color),
+            SkinPart::Flag => (&self.flag_material, self.flag_material.color),
+            // Faded flag
+            SkinPart::WrongFlag => (&self.flag_material, self.flag_material.color.with_alpha(0.35)),
+            SkinPart::Question => (&self.question_material, self.question_material.color),
+            SkinPart::Bomb => (&self.bomb_material, self.bomb_material.color),
+            // Red tinted bomb
+            SkinPart::ExplodedBomb => (&self.bomb_material, Color::srgb(1., 0.15, 0.15)),
+            SkinPart::BombCounter(_) => return None,
+        };
+        Some(SkinSprite {
+            texture: material.texture.clone(),
+            color,
+            atlas: None,
+        })
     }
 
     /// Safely retrieves th color matching a bomb counter
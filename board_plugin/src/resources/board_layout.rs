@@ -0,0 +1,276 @@
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::components::Coordinates;
+use crate::resources::board::Board;
+use crate::resources::tile::{Mark, Tile};
+use crate::resources::tile_map::TileMap;
+
+/// Hand written board, in a plain text grid with the top row first:
+///
+/// ```text
+/// # Corner cascade
+/// ...*.
+/// .....
+/// F1000
+/// ```
+///
+/// `.` is a covered safe tile and `*` a covered bomb, `0` to `8` an uncovered tile
+/// with its bomb count, `F` a flagged bomb, `f` a wrongly flagged safe tile and `X`
+/// an exploded bomb.
+/// Lines starting with `#` and blank lines are ignored
+#[derive(Debug, Clone)]
+pub struct BoardLayout {
+    pub tile_map: TileMap,
+    pub uncovered: Vec<Coordinates>,
+    pub flags: Vec<Coordinates>,
+}
+
+/// Errors raised while reading a board layout
+#[derive(Debug)]
+pub enum LayoutError {
+    Io(std::io::Error),
+    /// The layout has no row
+    Empty,
+    /// A row is wider or narrower than the first one, lines counted from 1
+    UnevenRow { line: usize, width: usize, expected: usize },
+    /// The layout is larger than a board can be
+    TooLarge,
+    UnknownCell { line: usize, column: usize, cell: char },
+    /// An uncovered tile doesn't show its bomb count
+    WrongCount { line: usize, column: usize, expected: u8 },
+}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not read layout file: {}", e),
+            Self::Empty => write!(f, "Layout has no row"),
+            Self::UnevenRow { line, width, expected } => write!(
+                f,
+                "Line {}: row of {} tiles, expected {}",
+                line, width, expected
+            ),
+            Self::TooLarge => write!(f, "Layout is too large"),
+            Self::UnknownCell { line, column, cell } => {
+                write!(f, "Line {}, column {}: unknown tile '{}'", line, column, cell)
+            }
+            Self::WrongCount { line, column, expected } => write!(
+                f,
+                "Line {}, column {}: uncovered tile has {} neighbor bombs",
+                line, column, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+impl From<std::io::Error> for LayoutError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl BoardLayout {
+    /// Reads a layout file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LayoutError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Retrieves the layout of a board, with its uncovered tiles and flags
+    pub fn from_board(board: &Board) -> Self {
+        Self {
+            tile_map: board.tile_map.clone(),
+            uncovered: board
+                .coordinates()
+                .filter(|c| !board.covered_tiles.contains(c))
+                .collect(),
+            flags: board
+                .marks()
+                .into_iter()
+                .filter(|(_, mark)| *mark == Mark::Flag)
+                .map(|(c, _)| c)
+                .collect(),
+        }
+    }
+
+    /// Generates a headless board from the layout, its tiles uncovered and flagged
+    pub fn into_board(self) -> Board {
+        let mut board = Board::headless(self.tile_map);
+        for coords in self.uncovered.iter() {
+            board.uncover_tile(coords);
+        }
+        for coords in self.flags.iter() {
+            board.apply_mark(coords, Some(Mark::Flag));
+        }
+        board
+    }
+}
+
+impl From<TileMap> for BoardLayout {
+    fn from(tile_map: TileMap) -> Self {
+        Self {
+            tile_map,
+            uncovered: Vec::new(),
+            flags: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for BoardLayout {
+    type Err = LayoutError;
+
+    fn from_str(s: &str) -> Result<Self, LayoutError> {
+        let rows: Vec<(usize, Vec<char>)> = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim_end().chars().collect()))
+            .filter(|(_, row): &(usize, Vec<char>)| !row.is_empty() && row[0] != '#')
+            .collect();
+        let width = rows.first().ok_or(LayoutError::Empty)?.1.len();
+        if let Some((line, row)) = rows.iter().find(|(_, row)| row.len() != width) {
+            return Err(LayoutError::UnevenRow {
+                line: *line,
+                width: row.len(),
+                expected: width,
+            });
+        }
+        let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(rows.len())) else {
+            return Err(LayoutError::TooLarge);
+        };
+        let mut bombs = Vec::new();
+        let mut uncovered = Vec::new();
+        let mut flags = Vec::new();
+        // Rows are written top first
+        let cells = rows.iter().rev().enumerate().flat_map(|(y, (line, row))| {
+            row.iter().enumerate().map(move |(x, cell)| {
+                let coords = Coordinates { x: x as i32, y: y as i32 };
+                (coords, (*line, x + 1), *cell)
+            })
+        });
+        let mut counts = Vec::new();
+        for (coords, (line, column), cell) in cells {
+            match cell {
+                '.' => (),
+                '*' => bombs.push(coords),
+                'F' => {
+                    bombs.push(coords);
+                    flags.push(coords);
+                }
+                'f' => flags.push(coords),
+                'X' => {
+                    bombs.push(coords);
+                    uncovered.push(coords);
+                }
+                '0'..='8' => {
+                    uncovered.push(coords);
+                    counts.push((coords, (line, column), cell as u8 - b'0'));
+                }
+                cell => return Err(LayoutError::UnknownCell { line, column, cell }),
+            }
+        }
+        let tile_map = TileMap::with_bombs(width, height, &bombs);
+        for (coords, (line, column), count) in counts {
+            let expected = tile_map.bomb_count_at(coords);
+            if expected != count {
+                return Err(LayoutError::WrongCount { line, column, expected });
+            }
+        }
+        Ok(Self {
+            tile_map,
+            uncovered,
+            flags,
+        })
+    }
+}
+
+impl Display for BoardLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for y in (0..self.tile_map.height() as i32).rev() {
+            for x in 0..self.tile_map.width() as i32 {
+                let coords = Coordinates { x, y };
+                let tile = self.tile_map.tile_at(coords).copied().unwrap_or(Tile::Empty);
+                let cell = match (self.uncovered.contains(&coords), self.flags.contains(&coords), tile) {
+                    (true, _, Tile::Bomb) => 'X',
+                    (false, true, Tile::Bomb) => 'F',
+                    (false, true, _) => 'f',
+                    (false, false, Tile::Bomb) => '*',
+                    (false, false, _) => '.',
+                    (true, _, _) => (b'0' + self.tile_map.bomb_count_at(coords)) as char,
+                };
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_round_trip() {
+        let text = "...*.\n.X2f.\nF2100\n";
+        let layout: BoardLayout = text.parse().unwrap();
+        assert_eq!(layout.tile_map.bomb_count(), 3);
+        assert_eq!(layout.flags.len(), 2);
+        assert_eq!(layout.to_string(), text);
+        let board = layout.into_board();
+        assert_eq!(BoardLayout::from_board(&board).to_string(), text);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_ignored() {
+        let layout: BoardLayout = "# Two rows\n\n.*\n\n11\n".parse().unwrap();
+        assert_eq!((layout.tile_map.width(), layout.tile_map.height()), (2, 2));
+        assert_eq!(layout.uncovered.len(), 2);
+    }
+
+    #[test]
+    fn bad_layouts_are_rejected() {
+        assert!(matches!("# Nothing\n".parse::<BoardLayout>(), Err(LayoutError::Empty)));
+        assert!(matches!(
+            "...\n..?\n".parse::<BoardLayout>(),
+            Err(LayoutError::UnknownCell { line: 2, column: 3, cell: '?' })
+        ));
+        assert!(matches!(
+            "# Ragged\n...\n....\n".parse::<BoardLayout>(),
+            Err(LayoutError::UnevenRow { line: 3, width: 4, expected: 3 })
+        ));
+        assert!(matches!(
+            ".*\n2.\n".parse::<BoardLayout>(),
+            Err(LayoutError::WrongCount { line: 2, column: 1, expected: 1 })
+        ));
+    }
+
+    #[test]
+    fn corner_cascade() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/layouts/corner_cascade.txt");
+        let mut board = BoardLayout::load(path).unwrap().into_board();
+        board.reveal(Coordinates { x: 7, y: 0 });
+        let covered = |board: &Board| -> Vec<(i32, i32)> {
+            board
+                .coordinates()
+                .filter(|c| board.covered_tiles.contains(c) && !board.tile_map.is_bomb_at(*c))
+                .map(|c| (c.x, c.y))
+                .collect()
+        };
+        // Only the safe tiles left of the bomb diagonal stay covered
+        let mut left = vec![(0, 1), (0, 2), (0, 3), (0, 4), (0, 5), (1, 3), (1, 4), (1, 5), (2, 5)];
+        let mut still_covered = covered(&board);
+        still_covered.sort();
+        assert_eq!(still_covered, left);
+        assert!(!board.is_exploded());
+        // The 1 next to the flag chords its last covered neighbor
+        let chorded = board.chord(Coordinates { x: 1, y: 0 });
+        assert_eq!(chorded, vec![(Coordinates { x: 0, y: 1 }, 0)]);
+        left.retain(|c| *c != (0, 1));
+        let mut still_covered = covered(&board);
+        still_covered.sort();
+        assert_eq!(still_covered, left);
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use bevy::prelude::{Resource, Vec3};
 use serde::{Deserialize, Serialize};
 
@@ -19,9 +21,63 @@ pub enum BoardOption {
     Custom(Vec3),
 }
 
+/// Cascade animation options
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub struct CascadeAnimation {
+    /// Delay between two rings of an opening, in seconds
+    pub ring_delay: f32,
+}
+
+/// Reveal, explosion and win animation options
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub struct AnimationOptions {
+    /// Duration of the cover fade and scale out, in seconds
+    pub reveal_duration: f32,
+    /// Delay before the explosion shockwave reveals a mine, in seconds per tile of distance
+    pub shockwave_delay: f32,
+    /// Duration of the win effect, in seconds
+    pub win_duration: f32,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            reveal_duration: 0.15,
+            shockwave_delay: 0.04,
+            win_duration: 2.5,
+        }
+    }
+}
+
+/// Game mode, deciding which moves can be undone
+#[derive(Debug,Copy,Clone,Default,Eq,PartialEq,Serialize,Deserialize)]
+pub enum GameMode {
+    /// Every move can be undone
+    #[default]
+    Practice,
+    /// Moves uncovering a bomb can't be undone
+    Ranked,
+    /// Logic puzzle of a pre-revealed layout: a wrong reveal can't be undone,
+    /// and the game isn't timed
+    Puzzle,
+}
+
+impl GameMode {
+    /// Can moves uncovering a bomb be undone
+    pub const fn allows_bomb_undo(&self) -> bool {
+        matches!(self, Self::Practice)
+    }
+
+    /// Does the game timer run
+    pub const fn is_timed(&self) -> bool {
+        !matches!(self, Self::Puzzle)
+    }
+}
+
 /// Board generation options. Must be used as a resource
 /// need Resource derive if this struct is resource type.
 #[derive(Debug,Clone,Serialize,Deserialize, Resource)]
+#[serde(default)]
 pub struct BoardOptions {
     /// Tile map size
     pub map_size:(u16,u16),
@@ -35,6 +91,19 @@ pub struct BoardOptions {
     pub tile_padding: f32,
     /// Does the board generate a safe place to start
     pub safe_start: bool,
+    /// Staggers the cover despawns of an opening, ring by ring
+    pub cascade_animation: Option<CascadeAnimation>,
+    /// Game mode
+    pub mode: GameMode,
+    /// Does marking cycle through a question mark after the flag
+    pub question_marks: bool,
+    /// Reveal, explosion and win animations, `None` disables them
+    pub animations: Option<AnimationOptions>,
+    /// Seed of the bomb placement, random when `None`
+    pub seed: Option<u64>,
+    /// `BoardLayout` text file, or `.mbf` board, played instead of a random board,
+    /// its size and bombs replacing `map_size` and `bomb_count`
+    pub layout: Option<PathBuf>,
 }
 
 impl Default for TileSize {
@@ -63,6 +132,12 @@ impl Default for BoardOptions {
             tile_size: Default::default(),
             tile_padding: 0.0,
             safe_start: false,
+            cascade_animation: None,
+            mode: Default::default(),
+            question_marks: false,
+            animations: Some(Default::default()),
+            seed: None,
+            layout: None,
         }
     }
 }
@@ -0,0 +1,25 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::components::KeyBindings;
+
+/// A player of the boards spawned by `BoardPlugin`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardPlayer {
+    pub name: String,
+    pub keys: KeyBindings,
+}
+
+/// Players of the boards spawned by `BoardPlugin`, each getting its own board,
+/// side by side in this order. The boards are identical, generated from the same seed
+#[derive(Debug, Clone, Serialize, Deserialize, Resource)]
+pub struct BoardPlayers(pub Vec<BoardPlayer>);
+
+impl Default for BoardPlayers {
+    fn default() -> Self {
+        Self(vec![BoardPlayer {
+            name: "Player".to_string(),
+            keys: Default::default(),
+        }])
+    }
+}
@@ -0,0 +1,288 @@
+use crate::components::Coordinates;
+use crate::resources::board::CHUNK_SIZE;
+use crate::resources::tile::{Mark, Tile};
+use crate::resources::tile_bitset::TileBitSet;
+use crate::resources::tile_map::SQUARE_COORDINATES;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Maximum number of tiles uncovered by a single opening
+const MAX_OPENING: usize = 1 << 16;
+
+/// Board extending in every direction, its bombs are generated chunk by chunk
+/// from the seed and the chunk coordinates
+#[derive(Debug, Resource)]
+pub struct EndlessBoard {
+    pub seed: u64,
+    pub bomb_density: f32,
+    pub tile_size: f32,
+    /// Generated bombs, by chunk coordinates, in chunk local coordinates
+    bombs: HashMap<Coordinates, TileBitSet>,
+    pub uncovered_tiles: HashSet<Coordinates>,
+    pub marked_tiles: HashSet<Coordinates>,
+    pub question_tiles: HashSet<Coordinates>,
+    /// Spawned chunk entities, by chunk coordinates
+    pub chunks: HashMap<Coordinates, Entity>,
+    pub entity: Entity,
+    /// Was a bomb uncovered
+    pub exploded: bool,
+}
+
+impl EndlessBoard {
+    pub fn new(seed: u64, bomb_density: f32, tile_size: f32, entity: Entity) -> Self {
+        Self {
+            seed,
+            bomb_density: bomb_density.clamp(0., 1.),
+            tile_size,
+            bombs: HashMap::new(),
+            uncovered_tiles: HashSet::new(),
+            marked_tiles: HashSet::new(),
+            question_tiles: HashSet::new(),
+            chunks: HashMap::new(),
+            entity,
+            exploded: false,
+        }
+    }
+
+    /// Retrieves the coordinates of the chunk holding `coords`
+    pub fn chunk_of(coords: Coordinates) -> Coordinates {
+        Coordinates {
+            x: coords.x.div_euclid(CHUNK_SIZE),
+            y: coords.y.div_euclid(CHUNK_SIZE),
+        }
+    }
+
+    fn chunk_seed(seed: u64, chunk: Coordinates) -> u64 {
+        // splitmix64 finalizer over the seed and the packed chunk coordinates
+        let mut z = seed ^ (((chunk.x as u32 as u64) << 32) | chunk.y as u32 as u64);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn generate_chunk(seed: u64, bomb_density: f32, chunk: Coordinates) -> TileBitSet {
+        let mut rng = StdRng::seed_from_u64(Self::chunk_seed(seed, chunk));
+        let mut bombs = TileBitSet::empty(CHUNK_SIZE as u16, CHUNK_SIZE as u16);
+        for y in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let bomb = rng.gen_bool(bomb_density as f64);
+                let (world_x, world_y) = (chunk.x * CHUNK_SIZE + x, chunk.y * CHUNK_SIZE + y);
+                // The tiles around the origin are kept safe to start from
+                if bomb && (world_x.abs() > 1 || world_y.abs() > 1) {
+                    bombs.insert(&Coordinates { x, y });
+                }
+            }
+        }
+        bombs
+    }
+
+    pub fn is_bomb_at(&mut self, coords: Coordinates) -> bool {
+        let (seed, bomb_density) = (self.seed, self.bomb_density);
+        let chunk = Self::chunk_of(coords);
+        let local = Coordinates {
+            x: coords.x.rem_euclid(CHUNK_SIZE),
+            y: coords.y.rem_euclid(CHUNK_SIZE),
+        };
+        self.bombs
+            .entry(chunk)
+            .or_insert_with(|| Self::generate_chunk(seed, bomb_density, chunk))
+            .contains(&local)
+    }
+
+    /// Retrieves the tile at `coords`, generating the chunks it depends on
+    pub fn tile_at(&mut self, coords: Coordinates) -> Tile {
+        if self.is_bomb_at(coords) {
+            return Tile::Bomb;
+        }
+        let mut count = 0;
+        for offset in SQUARE_COORDINATES {
+            if self.is_bomb_at(coords + offset) {
+                count += 1;
+            }
+        }
+        match count {
+            0 => Tile::Empty,
+            v => Tile::BombNeighbor(v),
+        }
+    }
+
+    pub fn is_covered(&self, coords: &Coordinates) -> bool {
+        !self.uncovered_tiles.contains(coords)
+    }
+
+    /// Uncovers a tile and the opening around it, returning the uncovered tiles.
+    /// Uncovering a bomb ends the game
+    pub fn try_uncover_tile(&mut self, coords: Coordinates) -> Vec<Coordinates> {
+        if self.exploded || !self.is_covered(&coords) || self.marked_tiles.contains(&coords) {
+            return Vec::new();
+        }
+        let mut uncovered = Vec::new();
+        let mut stack = vec![coords];
+        while let Some(coords) = stack.pop() {
+            if uncovered.len() >= MAX_OPENING {
+                break;
+            }
+            if !self.uncovered_tiles.insert(coords) {
+                continue;
+            }
+            self.marked_tiles.remove(&coords);
+            self.question_tiles.remove(&coords);
+            uncovered.push(coords);
+            match self.tile_at(coords) {
+                Tile::Bomb => {
+                    self.exploded = true;
+                    break;
+                }
+                Tile::Empty => stack.extend(
+                    SQUARE_COORDINATES
+                        .iter()
+                        .map(|offset| coords + *offset)
+                        .filter(|c| self.is_covered(c)),
+                ),
+                Tile::BombNeighbor(_) => (),
+            }
+        }
+        uncovered
+    }
+
+    /// Uncovers the unmarked neighbors of an uncovered bomb neighbor tile,
+    /// if it has as many marked neighbors as neighbor bombs
+    pub fn try_chord(&mut self, coords: Coordinates) -> Vec<Coordinates> {
+        if self.exploded || self.is_covered(&coords) {
+            return Vec::new();
+        }
+        let Tile::BombNeighbor(count) = self.tile_at(coords) else {
+            return Vec::new();
+        };
+        let neighbors: Vec<Coordinates> = SQUARE_COORDINATES
+            .iter()
+            .map(|offset| coords + *offset)
+            .collect();
+        let marked = neighbors.iter().filter(|c| self.marked_tiles.contains(*c)).count();
+        if marked != count as usize {
+            return Vec::new();
+        }
+        neighbors
+            .into_iter()
+            .flat_map(|c| self.try_uncover_tile(c))
+            .collect()
+    }
+
+    /// Retrieves the mark of a tile
+    pub fn mark_at(&self, coords: &Coordinates) -> Option<Mark> {
+        if self.marked_tiles.contains(coords) {
+            Some(Mark::Flag)
+        } else if self.question_tiles.contains(coords) {
+            Some(Mark::Question)
+        } else {
+            None
+        }
+    }
+
+    /// Cycles the mark of a covered tile through flag and, if `question_marks` is set,
+    /// question mark. Returns the new mark, or `None` if the tile isn't covered
+    pub fn toggle_mark(&mut self, coords: Coordinates, question_marks: bool) -> Option<Option<Mark>> {
+        if self.exploded || !self.is_covered(&coords) {
+            return None;
+        }
+        let to = match self.mark_at(&coords) {
+            None => Some(Mark::Flag),
+            Some(Mark::Flag) if question_marks => Some(Mark::Question),
+            Some(Mark::Flag) | Some(Mark::Question) => None,
+        };
+        self.marked_tiles.remove(&coords);
+        self.question_tiles.remove(&coords);
+        match to {
+            Some(Mark::Flag) => self.marked_tiles.insert(coords),
+            Some(Mark::Question) => self.question_tiles.insert(coords),
+            None => false,
+        };
+        Some(to)
+    }
+
+    /// Number of safe tiles uncovered
+    pub fn score(&self) -> usize {
+        self.uncovered_tiles.len() - self.exploded as usize
+    }
+
+    /// Translates a mouse world position to board coordinates
+    pub fn mouse_position(&self, position: Vec2) -> Coordinates {
+        let coordinates = (position / self.tile_size).floor();
+        Coordinates {
+            x: coordinates.x as i32,
+            y: coordinates.y as i32,
+        }
+    }
+
+    /// Retrieves the chunks overlapping the `min`..`max` world area
+    pub fn chunks_in_view(&self, min: Vec2, max: Vec2) -> Vec<Coordinates> {
+        let min = (min / (self.tile_size * CHUNK_SIZE as f32)).floor();
+        let max = (max / (self.tile_size * CHUNK_SIZE as f32)).floor();
+        let (min_x, max_x) = (min.x as i32, max.x as i32);
+        let (min_y, max_y) = (min.y as i32, max.y as i32);
+        (min_y..=max_y)
+            .flat_map(|y| (min_x..=max_x).map(move |x| Coordinates { x, y }))
+            .collect()
+    }
+
+    /// Forgets a spawned chunk, returning the chunk entity to despawn.
+    /// The chunk is spawned again from the board state when in view
+    pub fn remove_chunk(&mut self, chunk: Coordinates) -> Option<Entity> {
+        self.chunks.remove(&chunk)
+    }
+
+    /// Retrieves the tile coordinates of a chunk
+    pub fn chunk_tiles(chunk: Coordinates) -> impl Iterator<Item = Coordinates> {
+        let (start_x, start_y) = (chunk.x * CHUNK_SIZE, chunk.y * CHUNK_SIZE);
+        (start_y..start_y + CHUNK_SIZE)
+            .flat_map(move |y| (start_x..start_x + CHUNK_SIZE).map(move |x| Coordinates { x, y }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_in_view_cover_the_window() {
+        let board = EndlessBoard::new(0, 0.16, 10., Entity::PLACEHOLDER);
+        let chunk = 10. * CHUNK_SIZE as f32;
+        let chunks = board.chunks_in_view(Vec2::new(-1., -1.), Vec2::new(chunk, 0.5 * chunk));
+        let expected: Vec<Coordinates> = [(-1, -1), (0, -1), (1, -1), (-1, 0), (0, 0), (1, 0)]
+            .into_iter()
+            .map(|(x, y)| Coordinates { x, y })
+            .collect();
+        assert_eq!(chunks, expected);
+    }
+
+    #[test]
+    fn chunks_are_generated_from_the_seed() {
+        let mut a = EndlessBoard::new(42, 0.3, 10., Entity::PLACEHOLDER);
+        let mut b = EndlessBoard::new(42, 0.3, 10., Entity::PLACEHOLDER);
+        let far = Coordinates { x: -40, y: 75 };
+        let tiles: Vec<Tile> = EndlessBoard::chunk_tiles(far).map(|c| a.tile_at(c)).collect();
+        assert!(EndlessBoard::chunk_tiles(far).zip(tiles).all(|(c, tile)| b.tile_at(c) == tile));
+        // The start is safe
+        assert!(!a.try_uncover_tile(Coordinates::default()).is_empty());
+        assert!(!a.exploded);
+    }
+
+    #[test]
+    fn uncovering_removes_marks_and_stops_on_a_bomb() {
+        let mut board = EndlessBoard::new(0, 1., 10., Entity::PLACEHOLDER);
+        let flagged = Coordinates { x: 1, y: 0 };
+        assert_eq!(board.toggle_mark(flagged, false), Some(Some(Mark::Flag)));
+        let uncovered = board.try_uncover_tile(Coordinates::default());
+        assert_eq!(uncovered.len(), 9);
+        assert_eq!(board.mark_at(&flagged), None);
+        assert_eq!(board.toggle_mark(flagged, false), None);
+
+        let bomb = Coordinates { x: 2, y: 0 };
+        assert_eq!(board.try_uncover_tile(bomb), vec![bomb]);
+        assert!(board.exploded);
+        assert_eq!(board.score(), 9);
+        assert_eq!(board.toggle_mark(Coordinates { x: 3, y: 0 }, false), None);
+    }
+}
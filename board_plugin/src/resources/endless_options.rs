@@ -0,0 +1,29 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+/// Endless board generation options. Must be used as a resource
+#[derive(Debug,Clone,Serialize,Deserialize, Resource)]
+pub struct EndlessOptions {
+    /// Mine generation seed, random if not set
+    pub seed: Option<u64>,
+    /// Probability for a tile to be a bomb
+    pub bomb_density: f32,
+    /// Tile world size
+    pub tile_size: f32,
+    /// Padding between tiles
+    pub tile_padding: f32,
+    /// Camera pan speed, in world units per second
+    pub pan_speed: f32,
+}
+
+impl Default for EndlessOptions {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            bomb_density: 0.16,
+            tile_size: 30.0,
+            tile_padding: 1.0,
+            pan_speed: 600.0,
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// Outcome of the game of a board. Tiles can only be uncovered or marked while playing,
+/// undoing the last move of a finished practice game resumes it
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Component, Serialize, Deserialize)]
+pub enum GameStatus {
+    #[default]
+    Playing,
+    Won,
+    Lost,
+}
@@ -0,0 +1,17 @@
+use bevy::prelude::Component;
+use std::time::Duration;
+
+/// Time spent on the game of a board, running from the first uncovered tiles
+/// until the board is completed or a bomb explodes
+#[derive(Debug, Clone, Default, Component)]
+pub struct GameTimer {
+    pub elapsed: Duration,
+    pub running: bool,
+}
+
+impl GameTimer {
+    /// Elapsed whole seconds
+    pub fn seconds(&self) -> u64 {
+        self.elapsed.as_secs()
+    }
+}
@@ -0,0 +1,9 @@
+use bevy::prelude::Component;
+
+use crate::components::Coordinates;
+
+/// Tile of a board focused by the keyboard, moved with the board `KeyBindings`
+#[derive(Debug, Copy, Clone, Default, Component)]
+pub struct KeyboardCursor {
+    pub coordinates: Coordinates,
+}
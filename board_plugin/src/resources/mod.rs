@@ -1,8 +1,29 @@
-pub(crate) mod tile;
-pub(crate) mod tile_map;
+pub mod tile;
+pub mod tile_map;
+pub mod tile_bitset;
+pub mod move_history;
 pub use board_options::*;
 pub use board_assets::*;
+pub use endless_options::*;
+pub use theme::*;
+pub use sounds::*;
+pub use game_timer::*;
+pub use palette::*;
+pub use keyboard_cursor::*;
+pub use game_status::*;
+pub use board_players::*;
+pub use board_layout::*;
 
 mod board_options;
 mod board_assets;
-pub(crate) mod board;
+mod endless_options;
+mod theme;
+mod sounds;
+mod game_timer;
+mod palette;
+mod keyboard_cursor;
+mod game_status;
+mod board_players;
+mod board_layout;
+pub mod board;
+pub(crate) mod endless_board;
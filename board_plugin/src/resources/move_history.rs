@@ -0,0 +1,71 @@
+use crate::components::Coordinates;
+use crate::resources::tile::Mark;
+
+/// A player move, as recorded in the board history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Move {
+    /// Uncovered a tile and its opening
+    Reveal {
+        origin: Coordinates,
+        tiles: Vec<Coordinates>,
+        /// Marked tiles uncovered by the opening
+        unmarked: Vec<(Coordinates, Mark)>,
+    },
+    /// Uncovered the neighbors of a bomb neighbor tile with as many marked neighbors
+    Chord {
+        origin: Coordinates,
+        tiles: Vec<Coordinates>,
+        /// Marked tiles uncovered by the openings
+        unmarked: Vec<(Coordinates, Mark)>,
+    },
+    /// Changed the mark of a covered tile
+    Mark {
+        tile: Coordinates,
+        from: Option<Mark>,
+        to: Option<Mark>,
+    },
+}
+
+impl Move {
+    /// Retrieves the tiles changed by the move
+    pub fn tiles(&self) -> Vec<Coordinates> {
+        match self {
+            Move::Reveal { tiles, .. } | Move::Chord { tiles, .. } => tiles.clone(),
+            Move::Mark { tile, .. } => vec![*tile],
+        }
+    }
+}
+
+/// Undo and redo stacks of the moves played on a board
+#[derive(Debug, Clone, Default)]
+pub struct MoveHistory {
+    undo: Vec<Move>,
+    redo: Vec<Move>,
+}
+
+impl MoveHistory {
+    /// Records a new move, dropping the undone ones
+    pub fn push(&mut self, m: Move) {
+        self.undo.push(m);
+        self.redo.clear();
+    }
+
+    /// Last played move
+    pub fn last(&self) -> Option<&Move> {
+        self.undo.last()
+    }
+
+    /// Moves the last played move to the redo stack
+    pub fn undo(&mut self) -> Option<Move> {
+        let m = self.undo.pop()?;
+        self.redo.push(m.clone());
+        Some(m)
+    }
+
+    /// Moves the last undone move back to the played ones
+    pub fn redo(&mut self) -> Option<Move> {
+        let m = self.redo.pop()?;
+        self.undo.push(m.clone());
+        Some(m)
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Bomb counter color palettes, with a color for each count from 1 to 8
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Classic,
+    /// Safe for red-green (green weak) color blindness
+    Deuteranopia,
+    /// Safe for red-green (red weak) color blindness
+    Protanopia,
+    /// Safe for blue-yellow color blindness
+    Tritanopia,
+    /// Dark saturated colors, for light tiles
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 5] = [
+        Palette::Classic,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+        Palette::HighContrast,
+    ];
+
+    /// Retrieves the palette colors, from 1 bomb up to 8
+    pub fn colors(&self) -> [Color; 8] {
+        let rgb = match self {
+            Palette::Classic => [
+                (0.0, 0.0, 1.0),
+                (0.0, 0.5, 0.0),
+                (1.0, 0.0, 0.0),
+                (0.0, 0.0, 0.5),
+                (0.5, 0.0, 0.0),
+                (0.0, 0.5, 0.5),
+                (0.0, 0.0, 0.0),
+                (0.5, 0.5, 0.5),
+            ],
+            Palette::Deuteranopia => [
+                (0.0, 0.45, 0.7),
+                (0.9, 0.6, 0.0),
+                (0.8, 0.47, 0.65),
+                (0.0, 0.2, 0.45),
+                (0.55, 0.3, 0.0),
+                (0.34, 0.7, 0.91),
+                (0.0, 0.0, 0.0),
+                (0.35, 0.35, 0.35),
+            ],
+            Palette::Protanopia => [
+                (0.0, 0.45, 0.7),
+                (0.9, 0.6, 0.0),
+                (0.6, 0.4, 0.8),
+                (0.0, 0.2, 0.45),
+                (0.45, 0.35, 0.1),
+                (0.0, 0.6, 0.6),
+                (0.0, 0.0, 0.0),
+                (0.35, 0.35, 0.35),
+            ],
+            Palette::Tritanopia => [
+                (0.85, 0.15, 0.15),
+                (0.0, 0.5, 0.5),
+                (0.75, 0.2, 0.6),
+                (0.45, 0.0, 0.0),
+                (0.0, 0.3, 0.3),
+                (0.5, 0.1, 0.4),
+                (0.0, 0.0, 0.0),
+                (0.35, 0.35, 0.35),
+            ],
+            Palette::HighContrast => [
+                (0.0, 0.0, 1.0),
+                (0.0, 0.4, 0.0),
+                (0.8, 0.0, 0.0),
+                (0.2, 0.0, 0.5),
+                (0.5, 0.2, 0.0),
+                (0.0, 0.35, 0.4),
+                (0.0, 0.0, 0.0),
+                (0.3, 0.3, 0.3),
+            ],
+        };
+        rgb.map(|(r, g, b)| Color::srgb(r, g, b))
+    }
+
+    /// Retrieves the color of a bomb count
+    pub fn color(&self, count: u8) -> Color {
+        self.colors()[(count.clamp(1, 8) - 1) as usize]
+    }
+}
+
+/// Bomb counter style, overriding the theme. Must be used as a resource
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Resource)]
+pub struct NumberStyle {
+    /// Palette replacing the theme bomb counter colors
+    pub palette: Option<Palette>,
+    /// Adds a pattern of pips to bomb counters, so counts never rely on color alone
+    pub glyphs: bool,
+}
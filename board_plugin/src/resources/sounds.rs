@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::tile::Mark;
+
+/// Board sound effects
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum BoardSound {
+    /// A single tile was uncovered
+    Reveal,
+    /// An opening was uncovered
+    Cascade,
+    Flag,
+    Unflag,
+    Chord,
+    Explosion,
+    Win,
+    /// The game timer went through a second
+    Tick,
+}
+
+impl BoardSound {
+    /// Sound of uncovered tiles: a chord, an opening, or a single tile
+    pub const fn of_uncovered(count: usize, chord: bool) -> Self {
+        match (chord, count) {
+            (true, _) => Self::Chord,
+            (false, 2..) => Self::Cascade,
+            _ => Self::Reveal,
+        }
+    }
+
+    /// Sound of a mark change, a question mark replacing the flag
+    pub const fn of_mark(mark: Option<Mark>) -> Self {
+        match mark {
+            Some(Mark::Flag) => Self::Flag,
+            Some(Mark::Question) | None => Self::Unflag,
+        }
+    }
+}
+
+/// Sound effect handles, a missing sound stays silent
+#[derive(Debug, Clone, Default)]
+pub struct BoardSounds {
+    pub reveal: Option<Handle<AudioSource>>,
+    pub cascade: Option<Handle<AudioSource>>,
+    pub flag: Option<Handle<AudioSource>>,
+    pub unflag: Option<Handle<AudioSource>>,
+    pub chord: Option<Handle<AudioSource>>,
+    pub explosion: Option<Handle<AudioSource>>,
+    pub win: Option<Handle<AudioSource>>,
+    pub tick: Option<Handle<AudioSource>>,
+}
+
+impl BoardSounds {
+    /// Retrieves the handle of a sound effect
+    pub fn get(&self, sound: BoardSound) -> Option<&Handle<AudioSource>> {
+        match sound {
+            BoardSound::Reveal => self.reveal.as_ref(),
+            BoardSound::Cascade => self.cascade.as_ref(),
+            BoardSound::Flag => self.flag.as_ref(),
+            BoardSound::Unflag => self.unflag.as_ref(),
+            BoardSound::Chord => self.chord.as_ref(),
+            BoardSound::Explosion => self.explosion.as_ref(),
+            BoardSound::Win => self.win.as_ref(),
+            BoardSound::Tick => self.tick.as_ref(),
+        }
+    }
+}
+
+/// Sound settings. Must be used as a resource
+#[derive(Debug, Clone, Serialize, Deserialize, Resource)]
+pub struct SoundOptions {
+    /// Sound effects volume, from 0 to 1
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl SoundOptions {
+    /// Volume the sound effects are played at, `None` when they are off
+    pub fn playback_volume(&self) -> Option<f32> {
+        (!self.muted && self.volume > 0.).then(|| self.volume.min(1.))
+    }
+}
+
+impl Default for SoundOptions {
+    fn default() -> Self {
+        Self {
+            volume: 0.5,
+            muted: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncovered_sounds() {
+        assert_eq!(BoardSound::of_uncovered(1, false), BoardSound::Reveal);
+        assert_eq!(BoardSound::of_uncovered(2, false), BoardSound::Cascade);
+        assert_eq!(BoardSound::of_uncovered(120, false), BoardSound::Cascade);
+        assert_eq!(BoardSound::of_uncovered(1, true), BoardSound::Chord);
+        assert_eq!(BoardSound::of_uncovered(30, true), BoardSound::Chord);
+    }
+
+    #[test]
+    fn mark_sounds() {
+        assert_eq!(BoardSound::of_mark(Some(Mark::Flag)), BoardSound::Flag);
+        assert_eq!(BoardSound::of_mark(Some(Mark::Question)), BoardSound::Unflag);
+        assert_eq!(BoardSound::of_mark(None), BoardSound::Unflag);
+    }
+
+    #[test]
+    fn playback_volume() {
+        assert_eq!(SoundOptions::default().playback_volume(), Some(0.5));
+        assert_eq!(SoundOptions { volume: 3., muted: false }.playback_volume(), Some(1.));
+        assert_eq!(SoundOptions { volume: 0., muted: false }.playback_volume(), None);
+        assert_eq!(SoundOptions { volume: 0.5, muted: true }.playback_volume(), None);
+    }
+}
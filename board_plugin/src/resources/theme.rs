@@ -0,0 +1,216 @@
+use std::fmt::{self, Display, Formatter};
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::{AtlasIndices, BoardAssets, BoardSounds, Palette, SpriteMaterial, TileAtlas};
+
+/// Board theme, loaded from a `.theme.ron` file
+#[derive(Debug, Clone, Asset, TypePath)]
+pub struct Theme {
+    /// Board assets described by the theme
+    pub assets: BoardAssets,
+    /// Padding between tiles, overriding `BoardOptions::tile_padding`
+    pub tile_padding: Option<f32>,
+}
+
+/// Theme applied to the board. Changing it re-tints the spawned board
+#[derive(Debug, Clone, Resource)]
+pub struct BoardTheme(pub Handle<Theme>);
+
+/// Sprite material, as written in a theme file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaterialDescriptor {
+    /// sRGBA color
+    #[serde(default = "MaterialDescriptor::white")]
+    pub color: [f32; 4],
+    /// Texture path, relative to the assets folder
+    #[serde(default)]
+    pub texture: Option<String>,
+}
+
+impl MaterialDescriptor {
+    fn white() -> [f32; 4] {
+        [1., 1., 1., 1.]
+    }
+
+    fn load(&self, load_context: &mut LoadContext) -> SpriteMaterial {
+        let [r, g, b, a] = self.color;
+        SpriteMaterial {
+            color: Color::srgba(r, g, b, a),
+            texture: match &self.texture {
+                Some(path) => load_context.load(path.clone()),
+                None => Default::default(),
+            },
+        }
+    }
+}
+
+/// Tile sprite sheet, as written in a theme file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtlasDescriptor {
+    /// Sprite sheet path, relative to the assets folder
+    pub texture: String,
+    /// Size of a tile in the sprite sheet, in pixels
+    pub tile_size: (u32, u32),
+    pub columns: u32,
+    pub rows: u32,
+    /// Space between tiles in the sprite sheet, in pixels
+    #[serde(default)]
+    pub padding: Option<(u32, u32)>,
+    pub indices: AtlasIndices,
+}
+
+impl AtlasDescriptor {
+    fn load(&self, load_context: &mut LoadContext) -> TileAtlas {
+        let layout = TextureAtlasLayout::from_grid(
+            self.tile_size.into(),
+            self.columns,
+            self.rows,
+            self.padding.map(UVec2::from),
+            None,
+        );
+        TileAtlas {
+            texture: load_context.load(self.texture.clone()),
+            layout: load_context.add_labeled_asset("atlas_layout".to_string(), layout),
+            indices: self.indices.clone(),
+        }
+    }
+}
+
+/// Sound effect paths, relative to the assets folder, as written in a theme file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundsDescriptor {
+    pub reveal: Option<String>,
+    pub cascade: Option<String>,
+    pub flag: Option<String>,
+    pub unflag: Option<String>,
+    pub chord: Option<String>,
+    pub explosion: Option<String>,
+    pub win: Option<String>,
+    pub tick: Option<String>,
+}
+
+impl SoundsDescriptor {
+    fn load(&self, load_context: &mut LoadContext) -> BoardSounds {
+        let mut load = |path: &Option<String>| path.as_ref().map(|p| load_context.load(p.clone()));
+        BoardSounds {
+            reveal: load(&self.reveal),
+            cascade: load(&self.cascade),
+            flag: load(&self.flag),
+            unflag: load(&self.unflag),
+            chord: load(&self.chord),
+            explosion: load(&self.explosion),
+            win: load(&self.win),
+            tick: load(&self.tick),
+        }
+    }
+}
+
+/// Theme file content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeDescriptor {
+    pub label: String,
+    pub board: MaterialDescriptor,
+    pub tile: MaterialDescriptor,
+    pub covered_tile: MaterialDescriptor,
+    pub flag: MaterialDescriptor,
+    pub question: MaterialDescriptor,
+    pub bomb: MaterialDescriptor,
+    /// Bomb counter font path, relative to the assets folder
+    pub font: String,
+    /// Bomb counter sRGBA colors, from 1 bomb up
+    #[serde(default)]
+    pub bomb_counter_colors: Vec<[f32; 4]>,
+    /// Bomb counter palette, used when no color is set
+    #[serde(default)]
+    pub palette: Option<Palette>,
+    #[serde(default)]
+    pub tile_padding: Option<f32>,
+    /// Tile sprite sheet, replacing the tile materials and bomb counter texts
+    #[serde(default)]
+    pub atlas: Option<AtlasDescriptor>,
+    #[serde(default)]
+    pub sounds: SoundsDescriptor,
+}
+
+/// Errors raised while loading a theme file
+#[derive(Debug)]
+pub enum ThemeLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl Display for ThemeLoaderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not read theme file: {}", e),
+            Self::Ron(e) => write!(f, "Could not parse theme file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ThemeLoaderError {}
+
+impl From<std::io::Error> for ThemeLoaderError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for ThemeLoaderError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::Ron(e)
+    }
+}
+
+/// Loads `.theme.ron` files into `Theme` assets
+#[derive(Debug, Default)]
+pub struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    type Asset = Theme;
+    type Settings = ();
+    type Error = ThemeLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Theme, ThemeLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let descriptor: ThemeDescriptor = ron::de::from_bytes(&bytes)?;
+        let assets = BoardAssets {
+            label: descriptor.label.clone(),
+            board_material: descriptor.board.load(load_context),
+            tile_material: descriptor.tile.load(load_context),
+            covered_tile_material: descriptor.covered_tile.load(load_context),
+            bomb_counter_font: load_context.load(descriptor.font.clone()),
+            bomb_counter_colors: match descriptor.bomb_counter_colors.is_empty() {
+                true => descriptor.palette.unwrap_or_default().colors().to_vec(),
+                false => descriptor
+                    .bomb_counter_colors
+                    .iter()
+                    .map(|[r, g, b, a]| Color::srgba(*r, *g, *b, *a))
+                    .collect(),
+            },
+            flag_material: descriptor.flag.load(load_context),
+            question_material: descriptor.question.load(load_context),
+            bomb_material: descriptor.bomb.load(load_context),
+            atlas: descriptor.atlas.as_ref().map(|a| a.load(load_context)),
+            sounds: descriptor.sounds.load(load_context),
+        };
+        Ok(Theme {
+            assets,
+            tile_padding: descriptor.tile_padding,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
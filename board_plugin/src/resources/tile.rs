@@ -1,8 +1,9 @@
 #[cfg(feature = "debug")]
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
 /// Enum describing a Minesweeper tile
-#[derive(Debug, Copy,Clone,Eq,PartialEq)]
+#[derive(Debug, Copy,Clone,Eq,PartialEq,Serialize,Deserialize)]
 pub enum Tile {
     /// Is a bomb
     Bomb,
@@ -12,6 +13,15 @@ pub enum Tile {
     Empty,
 }
 
+/// Mark put by the player on a covered tile
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Mark {
+    /// Flagged as a bomb
+    Flag,
+    /// Unsure, the tile can still be uncovered
+    Question,
+}
+
 impl Tile {
     /// Is the tile a bomb?
     pub const fn is_bomb(&self) -> bool {
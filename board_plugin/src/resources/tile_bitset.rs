@@ -0,0 +1,92 @@
+use crate::components::Coordinates;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Dense bit set holding one bit per board tile
+#[derive(Debug, Clone)]
+pub struct TileBitSet {
+    width: u16,
+    height: u16,
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl TileBitSet {
+    /// Generates a set with no tile
+    pub fn empty(width: u16, height: u16) -> Self {
+        let tile_count = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            len: 0,
+            words: vec![0; tile_count.div_ceil(WORD_BITS)],
+        }
+    }
+
+    /// Generates a set containing every tile of the board
+    pub fn full(width: u16, height: u16) -> Self {
+        let mut set = Self::empty(width, height);
+        let tile_count = width as usize * height as usize;
+        set.words.fill(u64::MAX);
+        let trailing = tile_count % WORD_BITS;
+        if let (Some(last), true) = (set.words.last_mut(), trailing > 0) {
+            *last = (1 << trailing) - 1;
+        }
+        set.len = tile_count;
+        set
+    }
+
+    fn index(&self, coordinates: &Coordinates) -> Option<usize> {
+        let x = usize::try_from(coordinates.x).ok()?;
+        let y = usize::try_from(coordinates.y).ok()?;
+        if x >= self.width as usize || y >= self.height as usize {
+            return None;
+        }
+        Some(y * self.width as usize + x)
+    }
+
+    pub fn contains(&self, coordinates: &Coordinates) -> bool {
+        match self.index(coordinates) {
+            None => false,
+            Some(i) => self.words[i / WORD_BITS] & (1 << (i % WORD_BITS)) != 0,
+        }
+    }
+
+    /// Adds a tile, returning `false` if it was already in the set
+    pub fn insert(&mut self, coordinates: &Coordinates) -> bool {
+        let Some(i) = self.index(coordinates) else {
+            return false;
+        };
+        let word = &mut self.words[i / WORD_BITS];
+        let bit = 1 << (i % WORD_BITS);
+        if *word & bit != 0 {
+            return false;
+        }
+        *word |= bit;
+        self.len += 1;
+        true
+    }
+
+    /// Removes a tile, returning `false` if it was not in the set
+    pub fn remove(&mut self, coordinates: &Coordinates) -> bool {
+        let Some(i) = self.index(coordinates) else {
+            return false;
+        };
+        let word = &mut self.words[i / WORD_BITS];
+        let bit = 1 << (i % WORD_BITS);
+        if *word & bit == 0 {
+            return false;
+        }
+        *word &= !bit;
+        self.len -= 1;
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
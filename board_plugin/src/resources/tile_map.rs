@@ -1,9 +1,11 @@
 use crate::resources::tile::Tile;
 use crate::components::Coordinates;
+use crate::resources::tile_bitset::TileBitSet;
 use std::ops::{Deref,DerefMut};
-use rand::{thread_rng, Rng};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
-const SQUARE_COORDINATES: [(i8,i8);8] = [
+pub(crate) const SQUARE_COORDINATES: [(i8,i8);8] = [
     (-1, -1),
     ( 0, -1),
     ( 1, -1),
@@ -38,6 +40,15 @@ impl TileMap {
         }
     }
 
+    /// Generates the map of a board played on a server, where the covered tiles
+    /// are unknown and drawn empty until uncovered
+    pub fn hidden(width: u16, height: u16, bomb_count: u16) -> Self {
+        Self {
+            bomb_count,
+            ..Self::empty(width, height)
+        }
+    }
+
     #[cfg(feature = "debug")]
     pub fn console_output(&self) -> String {
         let mut buffer = format!(
@@ -63,11 +74,14 @@ impl TileMap {
             .map(move |tuple| coordinates + tuple)
     }
 
+    pub fn tile_at(&self, coordinates: Coordinates) -> Option<&Tile> {
+        let x = usize::try_from(coordinates.x).ok()?;
+        let y = usize::try_from(coordinates.y).ok()?;
+        self.map.get(y)?.get(x)
+    }
+
     pub fn is_bomb_at(&self, coordinates:Coordinates) -> bool {
-        if coordinates.x >= self.width || coordinates.y >= self.height {
-            return false;
-        }
-        self.map[coordinates.y as usize][coordinates.x as usize].is_bomb()
+        self.tile_at(coordinates).is_some_and(Tile::is_bomb)
     }
 
     pub fn bomb_count_at(&self, coordinates: Coordinates) -> u8 {
@@ -81,10 +95,11 @@ impl TileMap {
         res as u8
     }
 
-    pub fn set_bombs(&mut self, bomb_count:u16) {
+    /// Places the bombs from a seed, so boards of the same size and seed are identical
+    pub fn set_bombs(&mut self, bomb_count:u16, seed: u64) {
         self.bomb_count = bomb_count;
         let mut remaining_bombs = bomb_count;
-        let mut rng = thread_rng();
+        let mut rng = StdRng::seed_from_u64(seed);
 
         while remaining_bombs > 0 {
             let(x,y) = (
@@ -96,9 +111,27 @@ impl TileMap {
                 remaining_bombs -= 1;
             }
         }
+        self.set_bomb_neighbors();
+    }
+
+    /// Generates a map with bombs at the given coordinates, out of bounds ones being ignored
+    pub fn with_bombs(width: u16, height: u16, bombs: &[Coordinates]) -> Self {
+        let mut tile_map = Self::empty(width, height);
+        for coords in bombs {
+            if tile_map.tile_at(*coords).is_some_and(|t| !t.is_bomb()) {
+                tile_map[coords.y as usize][coords.x as usize] = Tile::Bomb;
+                tile_map.bomb_count += 1;
+            }
+        }
+        tile_map.set_bomb_neighbors();
+        tile_map
+    }
+
+    /// Counts the neighbor bombs of every safe tile
+    fn set_bomb_neighbors(&mut self) {
         for y in 0..self.height {
             for x in 0..self.width {
-                let coords = Coordinates { x, y};
+                let coords = Coordinates { x: x as i32, y: y as i32 };
                 if self.is_bomb_at(coords) {
                     continue;
                 }
@@ -112,6 +145,45 @@ impl TileMap {
         }
     }
 
+    /// Retrieves the tile the safe start uncovers, the first empty tile row by row
+    pub fn safe_start(&self) -> Option<Coordinates> {
+        self.iter().enumerate().find_map(|(y, line)| {
+            let x = line.iter().position(|tile| *tile == Tile::Empty)?;
+            Some(Coordinates { x: x as i32, y: y as i32 })
+        })
+    }
+
+    /// Bechtel's board benchmark value: the fewest clicks clearing the board, one per
+    /// opening plus one per number outside the openings
+    pub fn three_bv(&self) -> u32 {
+        let mut opened = TileBitSet::empty(self.width, self.height);
+        let mut three_bv = 0;
+        let coordinates = (0..self.height as i32)
+            .flat_map(|y| (0..self.width as i32).map(move |x| Coordinates { x, y }));
+        for coords in coordinates.clone() {
+            if self.tile_at(coords) != Some(&Tile::Empty) || !opened.insert(&coords) {
+                continue;
+            }
+            three_bv += 1;
+            let mut queue = vec![coords];
+            while let Some(coords) = queue.pop() {
+                if self.tile_at(coords) != Some(&Tile::Empty) {
+                    continue;
+                }
+                for neighbor in self.safe_square_at(coords) {
+                    if opened.insert(&neighbor) {
+                        queue.push(neighbor);
+                    }
+                }
+            }
+        }
+        let numbers = coordinates
+            .filter(|c| matches!(self.tile_at(*c), Some(Tile::BombNeighbor(_))))
+            .filter(|c| !opened.contains(c))
+            .count();
+        three_bv + numbers as u32
+    }
+
     pub fn width(&self) -> u16 {
         self.width
     }
@@ -0,0 +1,344 @@
+//! Minesweeper solver, reading only what a player sees: the uncovered numbers, the flags
+//! and the bomb count. Flags are trusted as bombs
+use std::collections::{BTreeSet, HashMap};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::components::Coordinates;
+use crate::resources::board::Board;
+use crate::resources::tile::Tile;
+use crate::resources::tile_map::TileMap;
+use crate::resources::{BoardLayout, BoardOptions};
+
+/// Covered tiles known from the uncovered numbers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deduction {
+    pub safe: Vec<Coordinates>,
+    pub bombs: Vec<Coordinates>,
+}
+
+impl Deduction {
+    pub fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.bombs.is_empty()
+    }
+}
+
+/// Number of bombs among a set of unknown tiles
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Constraint {
+    tiles: BTreeSet<Coordinates>,
+    bombs: usize,
+}
+
+/// Is a tile covered and not flagged
+fn is_unknown(board: &Board, coords: &Coordinates) -> bool {
+    board.covered_tiles.contains(coords) && !board.marked_tiles.contains(coords)
+}
+
+/// Neighbors of a tile inside the board
+fn neighbors(board: &Board, coords: Coordinates) -> impl Iterator<Item = Coordinates> + '_ {
+    board
+        .tile_map
+        .safe_square_at(coords)
+        .filter(|c| board.tile_map.tile_at(*c).is_some())
+}
+
+/// Constraint of each uncovered number next to unknown tiles
+fn constraints(board: &Board) -> Vec<Constraint> {
+    board
+        .coordinates()
+        .filter(|c| !board.covered_tiles.contains(c))
+        .filter_map(|coords| {
+            let Some(Tile::BombNeighbor(count)) = board.tile_map.tile_at(coords).copied() else {
+                return None;
+            };
+            let flags = neighbors(board, coords)
+                .filter(|c| board.marked_tiles.contains(c))
+                .count();
+            let tiles: BTreeSet<_> = neighbors(board, coords).filter(|c| is_unknown(board, c)).collect();
+            (!tiles.is_empty()).then(|| Constraint {
+                tiles,
+                bombs: (count as usize).saturating_sub(flags),
+            })
+        })
+        .collect()
+}
+
+/// Finds the covered tiles known to be safe or bombs. Each number is checked alone,
+/// then against each overlapping number, then the bomb count against the unknown tiles
+pub fn deduce(board: &Board) -> Deduction {
+    let constraints = constraints(board);
+    let mut known = Known::default();
+    for constraint in constraints.iter() {
+        known.settle(&constraint.tiles, constraint.bombs);
+    }
+    // Numbers sharing a tile
+    let mut by_tile: HashMap<Coordinates, Vec<usize>> = HashMap::new();
+    for (index, constraint) in constraints.iter().enumerate() {
+        for tile in constraint.tiles.iter() {
+            by_tile.entry(*tile).or_default().push(index);
+        }
+    }
+    let mut pairs = BTreeSet::new();
+    for indices in by_tile.values() {
+        for a in indices.iter() {
+            for b in indices.iter().filter(|b| *b != a) {
+                pairs.insert((*a, *b));
+            }
+        }
+    }
+    for (a, b) in pairs {
+        let (a, b) = (&constraints[a], &constraints[b]);
+        let only_a: BTreeSet<_> = a.tiles.difference(&b.tiles).copied().collect();
+        let only_b: BTreeSet<_> = b.tiles.difference(&a.tiles).copied().collect();
+        // `b` has more bombs than `a` could share: the tiles only in `b` hold the
+        // difference, and those only in `a` none
+        if b.bombs >= a.bombs && b.bombs - a.bombs == only_b.len() {
+            known.settle(&only_b, only_b.len());
+            known.settle(&only_a, 0);
+        }
+        if only_a.is_empty() {
+            known.settle(&only_b, b.bombs.saturating_sub(a.bombs));
+        }
+    }
+    // Bomb count
+    if known.safe.is_empty() && known.bombs.is_empty() {
+        let unknown: BTreeSet<_> = board.coordinates().filter(|c| is_unknown(board, c)).collect();
+        let bombs_left = (board.tile_map.bomb_count() as usize).saturating_sub(board.marked_tiles.len());
+        known.settle(&unknown, bombs_left);
+    }
+    Deduction {
+        safe: known.safe.into_iter().collect(),
+        bombs: known.bombs.into_iter().collect(),
+    }
+}
+
+/// Tiles found by `deduce` so far
+#[derive(Default)]
+struct Known {
+    safe: BTreeSet<Coordinates>,
+    bombs: BTreeSet<Coordinates>,
+}
+
+impl Known {
+    /// Settles the tiles holding no bomb, or only bombs
+    fn settle(&mut self, tiles: &BTreeSet<Coordinates>, bombs: usize) {
+        match bombs {
+            0 => self.safe.extend(tiles.iter().copied()),
+            bombs if bombs == tiles.len() => self.bombs.extend(tiles.iter().copied()),
+            _ => (),
+        }
+    }
+}
+
+/// Plays the deductions on a board until none is left, the board is completed or a
+/// wrong flag makes it explode. Returns whether anything was played
+pub fn play_deductions(board: &mut Board) -> bool {
+    let mut played = false;
+    loop {
+        let deduction = deduce(board);
+        if deduction.is_empty() || board.is_completed() || board.is_exploded() {
+            return played;
+        }
+        played = true;
+        for coords in deduction.bombs.iter() {
+            if !board.marked_tiles.contains(coords) {
+                board.toggle_mark(coords, false);
+            }
+        }
+        for coords in deduction.safe {
+            board.reveal(coords);
+        }
+    }
+}
+
+/// Can the board be completed from its uncovered tiles and flags without guessing
+pub fn is_solvable(board: &Board) -> bool {
+    let mut board = BoardLayout::from_board(board).into_board();
+    play_deductions(&mut board);
+    board.is_completed() && !board.is_exploded()
+}
+
+/// Covered tile least likely to be a bomb, from the numbers around each tile or the
+/// density of the bombs left. Ties are broken at random
+pub fn best_guess(board: &Board, rng: &mut impl Rng) -> Option<Coordinates> {
+    let unknown: Vec<_> = board.coordinates().filter(|c| is_unknown(board, c)).collect();
+    let bombs_left = (board.tile_map.bomb_count() as usize).saturating_sub(board.marked_tiles.len());
+    let density = bombs_left as f32 / unknown.len().max(1) as f32;
+    let mut risks: HashMap<Coordinates, f32> = HashMap::new();
+    for constraint in constraints(board) {
+        let risk = constraint.bombs as f32 / constraint.tiles.len() as f32;
+        for tile in constraint.tiles {
+            let entry = risks.entry(tile).or_insert(risk);
+            *entry = entry.max(risk);
+        }
+    }
+    let risk_of = |c: &Coordinates| risks.get(c).copied().unwrap_or(density);
+    let lowest = unknown.iter().map(risk_of).fold(f32::INFINITY, f32::min);
+    let candidates: Vec<_> = unknown.iter().filter(|c| risk_of(c) <= lowest).collect();
+    candidates.choose(rng).copied().copied()
+}
+
+/// Result of a game played by `play_game`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct BotGame {
+    pub won: bool,
+    /// Tiles uncovered without being deduced safe
+    pub guesses: u32,
+    /// Safe tiles left to uncover when the game ended
+    pub tiles_left: usize,
+}
+
+/// Plays a game to its end, guessing when nothing can be deduced
+pub fn play_game(board: &mut Board, rng: &mut impl Rng) -> BotGame {
+    let mut guesses = 0;
+    loop {
+        play_deductions(board);
+        if board.is_completed() || board.is_exploded() {
+            break;
+        }
+        let Some(guess) = best_guess(board, rng) else {
+            break;
+        };
+        guesses += 1;
+        board.reveal(guess);
+    }
+    let tiles_left = board
+        .coordinates()
+        .filter(|c| board.covered_tiles.contains(c) && !board.tile_map.is_bomb_at(*c))
+        .count();
+    BotGame {
+        won: board.is_completed() && !board.is_exploded(),
+        guesses,
+        tiles_left,
+    }
+}
+
+/// Board of a tile map with tiles revealed, their openings included, and flags placed
+fn puzzle_board(tile_map: &TileMap, reveals: &[Coordinates], flags: &[Coordinates]) -> Board {
+    let mut board = Board::headless(tile_map.clone());
+    for coords in reveals {
+        board.reveal(*coords);
+    }
+    for coords in flags {
+        board.toggle_mark(coords, false);
+    }
+    board
+}
+
+/// Generates a logic puzzle: a partially revealed board with flags, which `deduce`
+/// clears without guessing, so its solution is unique. The bombs are placed outside a
+/// start square opened first, the board is solved from there, revealing a tile next to
+/// the uncovered ones whenever stuck, then the revealed tiles not needed to solve it are
+/// covered again. `None` when the bombs don't fit outside the start square
+pub fn generate_puzzle(options: &BoardOptions, seed: u64, flag_count: usize) -> Option<BoardLayout> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (width, height) = options.map_size;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let start = Coordinates {
+        x: rng.gen_range(0..width) as i32,
+        y: rng.gen_range(0..height) as i32,
+    };
+    let mut tiles: Vec<_> = (0..height as i32)
+        .flat_map(|y| (0..width as i32).map(move |x| Coordinates { x, y }))
+        .filter(|c| (c.x - start.x).abs() > 1 || (c.y - start.y).abs() > 1)
+        .collect();
+    if tiles.len() < options.bomb_count as usize {
+        return None;
+    }
+    tiles.shuffle(&mut rng);
+    tiles.truncate(options.bomb_count as usize);
+    let mut board = Board::headless(TileMap::with_bombs(width, height, &tiles));
+    board.reveal(start);
+    let mut reveals = vec![start];
+    loop {
+        play_deductions(&mut board);
+        if board.is_completed() {
+            break;
+        }
+        let safe: Vec<_> = board
+            .coordinates()
+            .filter(|c| is_unknown(&board, c) && !board.tile_map.is_bomb_at(*c))
+            .collect();
+        let frontier: Vec<_> = safe
+            .iter()
+            .filter(|c| neighbors(&board, **c).any(|n| !board.covered_tiles.contains(&n)))
+            .collect();
+        // Tiles away from the uncovered ones only when none is next to them
+        let given = match frontier.choose(&mut rng) {
+            Some(given) => **given,
+            None => match safe.choose(&mut rng) {
+                Some(given) => *given,
+                None => break,
+            },
+        };
+        board.reveal(given);
+        reveals.push(given);
+    }
+    let tile_map = board.tile_map.clone();
+    // Flags on bombs next to the start opening
+    let opening = puzzle_board(&tile_map, &reveals[..1], &[]);
+    let mut flags: Vec<_> = opening
+        .coordinates()
+        .filter(|c| tile_map.is_bomb_at(*c))
+        .filter(|c| neighbors(&opening, *c).any(|n| !opening.covered_tiles.contains(&n)))
+        .collect();
+    flags.shuffle(&mut rng);
+    flags.truncate(flag_count);
+    // Covers the revealed tiles one at a time, as long as the puzzle stays solvable
+    let mut order: Vec<_> = (0..reveals.len()).collect();
+    order.shuffle(&mut rng);
+    let mut kept = vec![true; reveals.len()];
+    for index in order {
+        kept[index] = false;
+        let remaining: Vec<_> = reveals.iter().zip(kept.iter()).filter(|(_, k)| **k).map(|(c, _)| *c).collect();
+        if remaining.is_empty() || !is_solvable(&puzzle_board(&tile_map, &remaining, &flags)) {
+            kept[index] = true;
+        }
+    }
+    let remaining: Vec<_> = reveals.iter().zip(kept.iter()).filter(|(_, k)| **k).map(|(c, _)| *c).collect();
+    Some(BoardLayout::from_board(&puzzle_board(&tile_map, &remaining, &flags)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(map_size: (u16, u16), bomb_count: u16) -> BoardOptions {
+        BoardOptions {
+            map_size,
+            bomb_count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn generated_puzzle_is_solvable() {
+        let layout = generate_puzzle(&options((16, 16), 40), 11, 4).unwrap();
+        let board = layout.into_board();
+        assert!(!board.is_completed());
+        assert_eq!(board.marked_tiles.len(), 4);
+        assert!(is_solvable(&board));
+    }
+
+    #[test]
+    fn generated_puzzle_is_seeded() {
+        let first = generate_puzzle(&options((9, 9), 10), 7, 1).unwrap();
+        let second = generate_puzzle(&options((9, 9), 10), 7, 1).unwrap();
+        assert_eq!(first.to_string(), second.to_string());
+    }
+
+    #[test]
+    fn dense_board_puzzle() {
+        // Only the start square, and maybe a few tiles, is safe
+        let layout = generate_puzzle(&options((9, 9), 72), 3, 7).unwrap();
+        assert!(is_solvable(&layout.into_board()));
+        // Even a corner start square leaves no room for the bombs
+        assert!(generate_puzzle(&options((9, 9), 78), 3, 7).is_none());
+        assert!(generate_puzzle(&options((0, 9), 0), 3, 7).is_none());
+    }
+}
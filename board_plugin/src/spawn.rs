@@ -0,0 +1,434 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::components::{Bomb, BombNeighbor, Chunk, Coordinates, SkinPart};
+use crate::resources::board::Board;
+use crate::resources::endless_board::EndlessBoard;
+use crate::resources::tile::{Mark, Tile};
+use crate::resources::{BoardAssets, SkinSprite};
+use crate::systems::mark::spawn_mark;
+
+pub(crate) fn bomb_count_text_bundle(count: u8, board_assets: &BoardAssets, size: f32) -> Text2dBundle{
+    // We generate a text bundle
+    let color = board_assets.bomb_counter_colors(count);
+    Text2dBundle {
+        text: Text {
+            sections: vec![TextSection {
+                value: count.to_string(),
+                style: TextStyle {
+                    color,
+                    font: board_assets.bomb_counter_font.clone(),
+                    font_size: size,
+                },
+            }],
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(0., 0., 1.),
+        ..Default::default()
+    }
+}
+
+/// Spawns the sprite of a skin part, from the atlas when the skin has one
+pub(crate) fn spawn_skin_sprite<'a>(
+    parent: &'a mut ChildBuilder,
+    board_assets: &BoardAssets,
+    part: SkinPart,
+    size: f32,
+    transform: Transform,
+) -> EntityCommands<'a> {
+    let skin = board_assets
+        .skin_sprite(part)
+        .expect("bomb counters drawn as text have no sprite");
+    let mut cmd = parent.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: skin.color,
+            custom_size: Some(Vec2::splat(size)),
+            ..Default::default()
+        },
+        texture: skin.texture,
+        transform,
+        ..Default::default()
+    });
+    cmd.insert(part);
+    if let Some(atlas) = skin.atlas {
+        cmd.insert(atlas);
+    }
+    cmd
+}
+
+/// Spawns the bomb counter of a bomb neighbor tile, as an atlas sprite or a text
+pub(crate) fn spawn_bomb_counter<'a>(
+    parent: &'a mut ChildBuilder,
+    board_assets: &BoardAssets,
+    count: u8,
+    size: f32,
+    transform: Transform,
+) -> EntityCommands<'a> {
+    let part = SkinPart::BombCounter(count);
+    if board_assets.skin_sprite(part).is_some() {
+        spawn_skin_sprite(parent, board_assets, part, size, transform)
+    } else {
+        let mut cmd = parent.spawn(bomb_count_text_bundle(count, board_assets, size));
+        cmd.insert(part).insert(transform);
+        cmd
+    }
+}
+
+/// Materials of the batched chunk layers, shared by every chunk of a skin,
+/// with the texture rectangle of each layer
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkMaterials {
+    pub tile: (Handle<ColorMaterial>, Rect),
+    pub cover: (Handle<ColorMaterial>, Rect),
+}
+
+impl ChunkMaterials {
+    pub fn new(
+        board_assets: &BoardAssets,
+        materials: &mut Assets<ColorMaterial>,
+        atlas_layouts: &Assets<TextureAtlasLayout>,
+    ) -> Self {
+        let mut layer = |part| {
+            let skin = board_assets
+                .skin_sprite(part)
+                .expect("tiles and covers always have a sprite");
+            let uv = skin_uv(&skin, atlas_layouts);
+            let material = materials.add(ColorMaterial {
+                color: skin.color,
+                texture: Some(skin.texture),
+            });
+            (material, uv)
+        };
+        Self {
+            tile: layer(SkinPart::Tile),
+            cover: layer(SkinPart::Cover),
+        }
+    }
+}
+
+/// Texture coordinates of a skin sprite: its atlas rectangle, or the whole texture
+fn skin_uv(skin: &SkinSprite, atlas_layouts: &Assets<TextureAtlasLayout>) -> Rect {
+    skin.atlas
+        .as_ref()
+        .and_then(|atlas| {
+            let layout = atlas_layouts.get(&atlas.layout)?;
+            let rect = layout.textures.get(atlas.index)?.as_rect();
+            let size = layout.size.as_vec2();
+            Some(Rect::from_corners(rect.min / size, rect.max / size))
+        })
+        .unwrap_or(Rect::new(0., 0., 1., 1.))
+}
+
+/// Position of a tile center, relative to the board entity
+pub(crate) fn tile_center(coordinates: Coordinates, size: f32) -> Vec2 {
+    Vec2::new(coordinates.x as f32 + 0.5, coordinates.y as f32 + 0.5) * size
+}
+
+/// Mesh of squares of `size` around `centers`, each textured with the `uv` rectangle
+pub(crate) fn quads_mesh(centers: &[Vec2], size: f32, uv: Rect) -> Mesh {
+    let half = size / 2.;
+    let mut positions = Vec::with_capacity(centers.len() * 4);
+    let mut uvs = Vec::with_capacity(centers.len() * 4);
+    let mut indices = Vec::with_capacity(centers.len() * 6);
+    for (i, center) in centers.iter().enumerate() {
+        let (min, max) = (*center - half, *center + half);
+        positions.extend([
+            [min.x, min.y, 0.],
+            [max.x, min.y, 0.],
+            [max.x, max.y, 0.],
+            [min.x, max.y, 0.],
+        ]);
+        // Texture rows go down
+        uvs.extend([
+            [uv.min.x, uv.max.y],
+            [uv.max.x, uv.max.y],
+            [uv.max.x, uv.min.y],
+            [uv.min.x, uv.min.y],
+        ]);
+        let first = i as u32 * 4;
+        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    let normals = vec![[0., 0., 1.]; positions.len()];
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Despawns every chunk of a board, spawned again from the board state when in view
+pub(crate) fn despawn_chunks(commands: &mut Commands, board: &mut Board) {
+    let chunks: Vec<Coordinates> = board.chunks.keys().copied().collect();
+    for chunk in chunks {
+        if let Some(entity) = board.remove_chunk(chunk) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Despawns every chunk of an endless board, spawned again from the board state when in view
+pub(crate) fn despawn_endless_chunks(commands: &mut Commands, board: &mut EndlessBoard) {
+    let chunks: Vec<Coordinates> = board.chunks.keys().copied().collect();
+    for chunk in chunks {
+        if let Some(entity) = board.remove_chunk(chunk) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Drawn state of a tile of a chunk
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ChunkTile {
+    pub coordinates: Coordinates,
+    pub tile: Tile,
+    pub covered: bool,
+    pub mark: Option<Mark>,
+}
+
+impl ChunkTile {
+    /// Whether the tile is drawn without its cover: uncovered, or an unflagged bomb once
+    /// the board `exploded`
+    fn is_shown(&self, exploded: bool) -> bool {
+        !self.covered
+            || (exploded && self.tile == Tile::Bomb && self.mark != Some(Mark::Flag))
+    }
+}
+
+/// Retrieves the tiles of a chunk of a board
+pub(crate) fn board_chunk_tiles(board: &Board, chunk: Coordinates) -> Vec<ChunkTile> {
+    board
+        .chunk_tiles(chunk)
+        .filter_map(|coordinates| {
+            Some(ChunkTile {
+                coordinates,
+                tile: *board.tile_map.tile_at(coordinates)?,
+                covered: board.covered_tiles.contains(&coordinates),
+                mark: board.mark_at(&coordinates),
+            })
+        })
+        .collect()
+}
+
+/// Retrieves the tiles of a chunk of an endless board, generating the chunks they depend on
+pub(crate) fn endless_chunk_tiles(board: &mut EndlessBoard, chunk: Coordinates) -> Vec<ChunkTile> {
+    EndlessBoard::chunk_tiles(chunk)
+        .map(|coordinates| ChunkTile {
+            coordinates,
+            tile: board.tile_at(coordinates),
+            covered: board.is_covered(&coordinates),
+            mark: board.mark_at(&coordinates),
+        })
+        .collect()
+}
+
+/// Spawns a chunk from the state of its `tiles`. The tile backgrounds and the covers
+/// are drawn as one batched mesh each, so only the shown bombs and counters and the
+/// marks get an entity. Bombs are shown once the board `exploded`.
+/// Returns the chunk entity
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_chunk(
+    parent: &mut ChildBuilder,
+    chunk: Coordinates,
+    tiles: &[ChunkTile],
+    (size, padding): (f32, f32),
+    exploded: bool,
+    board_assets: &BoardAssets,
+    materials: &ChunkMaterials,
+    meshes: &mut Assets<Mesh>,
+) -> Entity {
+    let mut backgrounds = Vec::new();
+    let mut covers = Vec::new();
+    let mut cmd = parent.spawn(SpatialBundle::default());
+    cmd.insert(Name::new(format!("Chunk {}", chunk)))
+        .insert(Chunk(chunk))
+        .with_children(|parent| {
+            for chunk_tile in tiles {
+                let coordinates = chunk_tile.coordinates;
+                let center = tile_center(coordinates, size);
+                backgrounds.push(center);
+                if chunk_tile.is_shown(exploded) {
+                    let transform = Transform::from_translation(center.extend(2.));
+                    let content = match chunk_tile.tile {
+                        // A bomb is only uncovered by exploding it
+                        Tile::Bomb => {
+                            let part = if chunk_tile.covered { SkinPart::Bomb } else { SkinPart::ExplodedBomb };
+                            let mut cmd = spawn_skin_sprite(parent, board_assets, part, size - padding, transform);
+                            cmd.insert(Bomb);
+                            Some(cmd)
+                        }
+                        Tile::BombNeighbor(v) => {
+                            let mut cmd = spawn_bomb_counter(parent, board_assets, v, size - padding, transform);
+                            cmd.insert(BombNeighbor { count: v });
+                            Some(cmd)
+                        }
+                        Tile::Empty => None,
+                    };
+                    if let Some(mut content) = content {
+                        content.insert(Name::new(format!("Tile {}", coordinates)))
+                            .insert(coordinates);
+                    }
+                    continue;
+                }
+                covers.push(center);
+                let transform = Transform::from_translation(center.extend(4.));
+                let mut mark = match chunk_tile.mark {
+                    Some(Mark::Flag) if exploded && chunk_tile.tile != Tile::Bomb => {
+                        let mut cmd = spawn_skin_sprite(parent, board_assets, SkinPart::WrongFlag, size, transform);
+                        cmd.insert(Name::new("Wrong Flag"));
+                        cmd
+                    }
+                    Some(mark) => spawn_mark(parent, board_assets, mark, size, transform),
+                    None => continue,
+                };
+                mark.insert(coordinates);
+            }
+            let layers = [(backgrounds, &materials.tile, 1., "Tiles"), (covers, &materials.cover, 3., "Covers")];
+            for (centers, (material, uv), z, name) in layers {
+                if centers.is_empty() {
+                    continue;
+                }
+                parent
+                    .spawn(MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(meshes.add(quads_mesh(&centers, size - padding, *uv))),
+                        material: material.clone(),
+                        transform: Transform::from_xyz(0., 0., z),
+                        ..Default::default()
+                    })
+                    .insert(Name::new(name));
+            }
+        });
+    cmd.id()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::resources::board::CHUNK_SIZE;
+    use crate::resources::{BoardLayout, SpriteMaterial};
+    use bevy::ecs::world::CommandQueue;
+
+    pub(crate) fn board_assets() -> BoardAssets {
+        BoardAssets {
+            label: "Test".to_string(),
+            board_material: SpriteMaterial::default(),
+            tile_material: SpriteMaterial::default(),
+            covered_tile_material: SpriteMaterial::default(),
+            bomb_counter_font: Default::default(),
+            bomb_counter_colors: BoardAssets::default_color(),
+            flag_material: SpriteMaterial::default(),
+            question_material: SpriteMaterial::default(),
+            bomb_material: SpriteMaterial::default(),
+            atlas: None,
+            sounds: Default::default(),
+        }
+    }
+
+    /// Spawns the first chunk of a layout, returning the skin parts of its entities
+    /// and the quad counts of its tile and cover meshes
+    fn spawn_layout_chunk(layout: &str) -> (Vec<SkinPart>, Vec<usize>) {
+        let board = layout.parse::<BoardLayout>().unwrap().into_board();
+        let tiles = board_chunk_tiles(&board, Coordinates::default());
+        spawn_test_chunk(&tiles, board.is_exploded())
+    }
+
+    /// Spawns a chunk of `tiles`, returning the skin parts of its entities
+    /// and the quad counts of its tile and cover meshes
+    fn spawn_test_chunk(tiles: &[ChunkTile], exploded: bool) -> (Vec<SkinPart>, Vec<usize>) {
+        let board_assets = board_assets();
+        let mut meshes = Assets::<Mesh>::default();
+        let mut materials = Assets::<ColorMaterial>::default();
+        let chunk_materials = ChunkMaterials::new(&board_assets, &mut materials, &Assets::default());
+        let mut world = World::new();
+        let root = world.spawn_empty().id();
+        let mut queue = CommandQueue::default();
+        let mut chunk = Entity::PLACEHOLDER;
+        Commands::new(&mut queue, &world).entity(root).with_children(|parent| {
+            let origin = Coordinates::default();
+            chunk = spawn_chunk(parent, origin, tiles, (10., 0.), exploded, &board_assets, &chunk_materials, &mut meshes);
+        });
+        queue.apply(&mut world);
+
+        let children = world.get::<Children>(chunk).unwrap();
+        let mut parts: Vec<SkinPart> = children
+            .iter()
+            .filter_map(|e| world.get::<SkinPart>(*e).copied())
+            .collect();
+        parts.sort();
+        let quads = children
+            .iter()
+            .filter_map(|e| world.get::<Mesh2dHandle>(*e))
+            .map(|mesh| meshes.get(&mesh.0).unwrap().count_vertices() / 4)
+            .collect();
+        (parts, quads)
+    }
+
+    #[test]
+    fn chunk_spawns_shown_tiles_only() {
+        let (parts, quads) = spawn_layout_chunk("..*\n.11\n000\n");
+        assert_eq!(parts, vec![SkinPart::BombCounter(1); 2]);
+        assert_eq!(quads, vec![9, 4]);
+    }
+
+    #[test]
+    fn exploded_chunk_shows_bombs_and_wrong_flags() {
+        let (parts, quads) = spawn_layout_chunk("...*.\n.X2f.\nF2100\n");
+        let mut expected = vec![
+            SkinPart::Flag,
+            SkinPart::WrongFlag,
+            SkinPart::Bomb,
+            SkinPart::ExplodedBomb,
+            SkinPart::BombCounter(2),
+            SkinPart::BombCounter(2),
+            SkinPart::BombCounter(1),
+        ];
+        expected.sort();
+        assert_eq!(parts, expected);
+        // Covers of the safe tiles and the flagged ones
+        assert_eq!(quads, vec![15, 8]);
+    }
+
+    #[test]
+    fn endless_chunk_spawns_the_start_opening() {
+        // Bombs everywhere but around the origin
+        let mut board = EndlessBoard::new(0, 1., 10., Entity::PLACEHOLDER);
+        board.try_uncover_tile(Coordinates::default());
+        let tiles = endless_chunk_tiles(&mut board, Coordinates::default());
+        let (parts, quads) = spawn_test_chunk(&tiles, board.exploded);
+        let mut expected = vec![
+            SkinPart::BombCounter(3),
+            SkinPart::BombCounter(3),
+            SkinPart::BombCounter(5),
+        ];
+        expected.sort();
+        assert_eq!(parts, expected);
+        let side = CHUNK_SIZE as usize;
+        assert_eq!(quads, vec![side * side, side * side - 4]);
+    }
+
+    #[test]
+    fn quads_are_textured_with_the_skin_rectangle() {
+        let mut atlas_layouts = Assets::<TextureAtlasLayout>::default();
+        let layout = atlas_layouts.add(TextureAtlasLayout::from_grid(UVec2::splat(16), 4, 1, None, None));
+        let skin = SkinSprite {
+            texture: Default::default(),
+            color: Color::WHITE,
+            atlas: Some(TextureAtlas { layout, index: 2 }),
+        };
+        let uv = skin_uv(&skin, &atlas_layouts);
+        assert_eq!(uv, Rect::new(0.5, 0., 0.75, 1.));
+
+        let mesh = quads_mesh(&[Vec2::new(5., 5.), Vec2::new(15., 5.)], 8., uv);
+        assert_eq!(mesh.count_vertices(), 8);
+        assert_eq!(mesh.indices().map(|i| i.len()), Some(12));
+        let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("quads have 3D positions");
+        };
+        assert_eq!(positions[4], [11., 1., 0.]);
+        assert_eq!(positions[6], [19., 9., 0.]);
+    }
+}
@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+/// Whether the board of `BoardPlugin` is spawned, following its `running_state`
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum BoardState {
+    #[default]
+    Despawned,
+    Spawned,
+}
+
+/// Pause of the spawned board. While paused the board is hidden,
+/// input is ignored and the game timer is frozen
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates)]
+#[source(BoardState = BoardState::Spawned)]
+pub enum BoardPause {
+    #[default]
+    Running,
+    Paused,
+}
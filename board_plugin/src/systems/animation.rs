@@ -0,0 +1,207 @@
+use crate::components::{Coordinates, FadeOut, Particle, SkinPart, Uncover};
+use crate::events::{BoardCompletedEvent, BombExplosionEvent};
+use crate::resources::AnimationOptions;
+use crate::spawn::{despawn_chunks, spawn_skin_sprite, tile_center};
+use crate::{Board, BoardAssets, BoardOptions};
+use bevy::log;
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+/// Number of particles of the explosion burst
+const EXPLOSION_PARTICLES: usize = 40;
+/// Number of particles of the win confetti
+const WIN_PARTICLES: usize = 150;
+/// Most covers animated at once, larger openings are uncovered at once
+pub(crate) const MAX_ANIMATED_COVERS: usize = 2048;
+
+/// Spawns a cover over a tile its chunk now draws uncovered, removed after `delay`,
+/// so uncovering keeps its cascade and fade out. Nothing is spawned without either
+pub(crate) fn spawn_fading_cover(
+    commands: &mut Commands,
+    board: &Board,
+    board_assets: &BoardAssets,
+    (coordinates, delay): (Coordinates, f32),
+    board_options: &BoardOptions,
+) {
+    let fades = matches!(&board_options.animations, Some(a) if a.reveal_duration > 0.);
+    if delay <= 0. && !fades {
+        return;
+    }
+    let mut cover = Entity::PLACEHOLDER;
+    commands.entity(board.entity).with_children(|parent| {
+        let position = tile_center(coordinates, board.tile_size).extend(3.);
+        cover = spawn_skin_sprite(
+            parent,
+            board_assets,
+            SkinPart::Cover,
+            board.tile_size - board_options.tile_padding,
+            Transform::from_translation(position),
+        )
+        .insert(Name::new("Tile Cover"))
+        .id();
+    });
+    if delay > 0. {
+        commands.entity(cover).insert(Uncover {
+            delay: Timer::from_seconds(delay, TimerMode::Once),
+        });
+    } else {
+        remove_cover(commands, cover, &board_options.animations);
+    }
+}
+
+/// Removes an uncovered tile cover, fading it out when animations are enabled
+pub(crate) fn remove_cover(
+    commands: &mut Commands,
+    cover: Entity,
+    animations: &Option<AnimationOptions>,
+) {
+    match animations {
+        Some(animations) if animations.reveal_duration > 0. => {
+            commands.entity(cover).insert(FadeOut {
+                timer: Timer::from_seconds(animations.reveal_duration, TimerMode::Once),
+            });
+        }
+        _ => commands.entity(cover).despawn_recursive(),
+    }
+}
+
+/// Shrinks and fades out the uncovered tile covers, despawning them once done
+pub fn fade_out_covers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut covers: Query<(Entity, &mut FadeOut, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut fade_out, mut transform, mut sprite) in covers.iter_mut() {
+        if fade_out.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let remaining = 1. - fade_out.timer.fraction();
+        transform.scale = Vec3::new(remaining, remaining, 1.);
+        sprite.color.set_alpha(remaining);
+    }
+}
+
+/// Reveals the remaining bombs and the wrong flags of a board from the exploded bomb,
+/// in distance order, with a burst of particles
+pub fn explosion_shockwave(
+    mut commands: Commands,
+    mut boards: Query<&mut Board>,
+    board_options: Res<BoardOptions>,
+    board_assets: Res<BoardAssets>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+) {
+    for event in bomb_explosion_evr.read() {
+        let Ok(mut board) = boards.get_mut(event.board) else {
+            continue;
+        };
+        let bombs = board.hidden_bombs_by_distance(event.coordinates);
+        log::debug!("Shockwave from {} reveals {} bombs", event.coordinates, bombs.len());
+        let animations = board_options
+            .animations
+            .as_ref()
+            .filter(|_| bombs.len() <= MAX_ANIMATED_COVERS);
+        if let Some(animations) = animations {
+            for (coordinates, distance) in bombs {
+                if board.chunks.contains_key(&Board::chunk_of(coordinates)) {
+                    let cover = (coordinates, animations.shockwave_delay * distance);
+                    spawn_fading_cover(&mut commands, &board, &board_assets, cover, &board_options);
+                }
+            }
+        }
+        // Exploded boards are spawned again with their bombs and wrong flags shown
+        despawn_chunks(&mut commands, &mut board);
+        if board_options.animations.is_some() {
+            let origin = tile_center(event.coordinates, board.tile_size);
+            let size = board.tile_size;
+            let mut rng = thread_rng();
+            commands.entity(board.entity).with_children(|parent| {
+                for _ in 0..EXPLOSION_PARTICLES {
+                    let direction = Vec2::from_angle(rng.gen_range(0. ..std::f32::consts::TAU));
+                    let color = Color::srgb(1., rng.gen_range(0.2..0.8), 0.1);
+                    spawn_particle(parent, Particle {
+                        velocity: direction * size * rng.gen_range(2. ..8.),
+                        gravity: 0.,
+                        lifetime: Timer::from_seconds(rng.gen_range(0.3..0.7), TimerMode::Once),
+                    }, origin, color, size / 5.);
+                }
+            });
+        }
+    }
+}
+
+/// Throws confetti over the completed boards
+pub fn win_effect(
+    mut commands: Commands,
+    boards: Query<&Board>,
+    board_options: Res<BoardOptions>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+) {
+    let Some(animations) = &board_options.animations else {
+        return;
+    };
+    for event in board_completed_evr.read() {
+        if let Ok(board) = boards.get(event.board) {
+            throw_confetti(&mut commands, board, animations);
+        }
+    }
+}
+
+fn throw_confetti(commands: &mut Commands, board: &Board, animations: &AnimationOptions) {
+    let height = board.bounds.size.y;
+    let origin = Vec2::new(board.bounds.size.x / 2., 0.);
+    let mut rng = thread_rng();
+    commands.entity(board.entity).with_children(|parent| {
+        for _ in 0..WIN_PARTICLES {
+            let velocity = Vec2::new(rng.gen_range(-0.6..0.6), rng.gen_range(0.9..1.6)) * height;
+            let color = Color::hsl(rng.gen_range(0. ..360.), 0.9, 0.6);
+            spawn_particle(parent, Particle {
+                velocity,
+                gravity: height * 1.2,
+                lifetime: Timer::from_seconds(
+                    animations.win_duration * rng.gen_range(0.6..1.),
+                    TimerMode::Once,
+                ),
+            }, origin, color, board.tile_size / 3.);
+        }
+    });
+}
+
+/// Moves the particles and fades them out along their lifetime
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        particle.velocity.y -= particle.gravity * delta;
+        transform.translation += (particle.velocity * delta).extend(0.);
+        sprite.color.set_alpha(1. - particle.lifetime.fraction());
+    }
+}
+
+fn spawn_particle(
+    parent: &mut ChildBuilder,
+    particle: Particle,
+    position: Vec2,
+    color: Color,
+    size: f32,
+) {
+    parent
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::splat(size)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position.extend(10.)),
+            ..Default::default()
+        })
+        .insert(Name::new("Particle"))
+        .insert(particle);
+}
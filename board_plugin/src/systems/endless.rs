@@ -0,0 +1,232 @@
+use crate::components::{Coordinates, ScoreText};
+use crate::events::{BombExplosionEvent, TileMarkEvent, TileTriggerEvent};
+use crate::resources::endless_board::EndlessBoard;
+use crate::resources::{BoardOptions, EndlessOptions};
+use crate::spawn::despawn_endless_chunks;
+use bevy::input::{mouse::MouseButtonInput, ButtonState};
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy::window::PrimaryWindow;
+
+pub fn input_handling(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    board: Res<EndlessBoard>,
+    mut button_evr: EventReader<MouseButtonInput>,
+    mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
+    mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+) {
+    let window = windows.single();
+    let Some((camera, camera_transform)) = cameras.iter().find(|(c, _)| c.is_active) else {
+        return;
+    };
+
+    for event in button_evr.read() {
+        if let ButtonState::Pressed = event.state {
+            let position = window
+                .cursor_position()
+                .and_then(|p| camera.viewport_to_world_2d(camera_transform, p));
+            if let Some(pos) = position {
+                let coordinates = board.mouse_position(pos);
+                match event.button {
+                    MouseButton::Left => {
+                        log::info!("Trying to uncover tile on {}", coordinates);
+                        tile_trigger_ewr.send(TileTriggerEvent {
+                            board: board.entity,
+                            coordinates,
+                        });
+                    }
+                    MouseButton::Right => {
+                        log::info!("Trying to mark tile on {}", coordinates);
+                        tile_mark_ewr.send(TileMarkEvent {
+                            board: board.entity,
+                            coordinates,
+                        });
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+/// Uncovers covered tiles, and chords uncovered ones. The chunks of the uncovered tiles
+/// are spawned again from the board state, every chunk once a bomb shows the others
+pub fn trigger_event_handler(
+    mut commands: Commands,
+    mut board: ResMut<EndlessBoard>,
+    mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut bomb_explosion_event_wr: EventWriter<BombExplosionEvent>,
+) {
+    for trigger_event in tile_trigger_evr.read() {
+        if trigger_event.board != board.entity {
+            continue;
+        }
+        let coords = trigger_event.coordinates;
+        let uncovered = if board.is_covered(&coords) {
+            board.try_uncover_tile(coords)
+        } else {
+            board.try_chord(coords)
+        };
+        log::debug!("Uncovered {} tiles from {}", uncovered.len(), coords);
+        let chunks: HashSet<Coordinates> = uncovered.iter().map(|c| EndlessBoard::chunk_of(*c)).collect();
+        for chunk in chunks {
+            if let Some(entity) = board.remove_chunk(chunk) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        // Uncovering stops on the exploded bomb
+        if let (true, Some(bomb)) = (board.exploded, uncovered.last().copied()) {
+            log::info!("Bomb !");
+            despawn_endless_chunks(&mut commands, &mut board);
+            bomb_explosion_event_wr.send(BombExplosionEvent {
+                board: board.entity,
+                coordinates: bomb,
+            });
+            break;
+        }
+    }
+}
+
+/// Cycles the mark of covered tiles, through question marks when the board options
+/// have them, and spawns their chunk again
+pub fn mark_tiles(
+    mut commands: Commands,
+    mut board: ResMut<EndlessBoard>,
+    board_options: Option<Res<BoardOptions>>,
+    mut tile_mark_event_rdr: EventReader<TileMarkEvent>,
+) {
+    let question_marks = board_options.is_some_and(|o| o.question_marks);
+    for event in tile_mark_event_rdr.read() {
+        if event.board != board.entity {
+            continue;
+        }
+        if board.toggle_mark(event.coordinates, question_marks).is_none() {
+            continue;
+        }
+        if let Some(entity) = board.remove_chunk(EndlessBoard::chunk_of(event.coordinates)) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Moves the camera with the arrow keys
+pub fn pan_camera(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    options: Res<EndlessOptions>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+) {
+    let mut direction = Vec2::ZERO;
+    if keys.pressed(KeyCode::ArrowLeft) {
+        direction.x -= 1.;
+    }
+    if keys.pressed(KeyCode::ArrowRight) {
+        direction.x += 1.;
+    }
+    if keys.pressed(KeyCode::ArrowDown) {
+        direction.y -= 1.;
+    }
+    if keys.pressed(KeyCode::ArrowUp) {
+        direction.y += 1.;
+    }
+    if direction == Vec2::ZERO {
+        return;
+    }
+    let offset = direction.normalize() * options.pan_speed * time.delta_seconds();
+    for mut transform in cameras.iter_mut() {
+        transform.translation += offset.extend(0.);
+    }
+}
+
+pub fn update_score_text(
+    board: Res<EndlessBoard>,
+    mut texts: Query<&mut Text, With<ScoreText>>,
+) {
+    if !board.is_changed() {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        if let Some(section) = text.sections.first_mut() {
+            section.value = format!("Score: {}", board.score());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::tile::Mark;
+
+    /// App playing an endless board full of bombs but around the origin,
+    /// with the 3x3 chunks around the origin spawned as empty entities
+    fn endless_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_event::<TileTriggerEvent>()
+            .add_event::<TileMarkEvent>()
+            .add_event::<BombExplosionEvent>();
+        let entity = app.world_mut().spawn_empty().id();
+        let mut board = EndlessBoard::new(0, 1., 10., entity);
+        for y in -1..=1 {
+            for x in -1..=1 {
+                board.chunks.insert(Coordinates { x, y }, app.world_mut().spawn_empty().id());
+            }
+        }
+        app.insert_resource(board);
+        (app, entity)
+    }
+
+    fn spawned_chunks(app: &App) -> usize {
+        app.world().resource::<EndlessBoard>().chunks.len()
+    }
+
+    #[test]
+    fn uncover_rebuilds_its_chunk_only() {
+        let (mut app, board) = endless_app();
+        app.add_systems(Update, trigger_event_handler);
+        app.world_mut().send_event(TileTriggerEvent {
+            board,
+            coordinates: Coordinates { x: -1, y: 1 },
+        });
+        app.update();
+        assert_eq!(spawned_chunks(&app), 8);
+        let board = app.world().resource::<EndlessBoard>();
+        assert!(!board.chunks.contains_key(&Coordinates { x: -1, y: 0 }));
+        assert!(!board.exploded);
+        assert_eq!(board.score(), 1);
+    }
+
+    #[test]
+    fn explosion_rebuilds_every_chunk() {
+        let (mut app, board) = endless_app();
+        app.add_systems(Update, trigger_event_handler);
+        app.world_mut().send_event(TileTriggerEvent {
+            board,
+            coordinates: Coordinates { x: 2, y: 2 },
+        });
+        app.update();
+        assert_eq!(spawned_chunks(&app), 0);
+        assert!(app.world().resource::<EndlessBoard>().exploded);
+        let explosions = app.world().resource::<Events<BombExplosionEvent>>();
+        assert_eq!(explosions.len(), 1);
+    }
+
+    #[test]
+    fn marks_cycle_through_question_marks() {
+        let (mut app, board) = endless_app();
+        app.insert_resource(BoardOptions {
+            question_marks: true,
+            ..Default::default()
+        })
+        .add_systems(Update, mark_tiles);
+        let coordinates = Coordinates { x: 5, y: 5 };
+        for expected in [Some(Mark::Flag), Some(Mark::Question), None] {
+            app.world_mut().send_event(TileMarkEvent { board, coordinates });
+            app.update();
+            let board = app.world().resource::<EndlessBoard>();
+            assert_eq!(board.mark_at(&coordinates), expected);
+            assert_eq!(board.chunks.len(), 8);
+        }
+    }
+}
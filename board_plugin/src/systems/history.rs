@@ -0,0 +1,64 @@
+use crate::components::RemoteBoard;
+use crate::events::{BoardCompletedEvent, BombExplosionEvent, RedoEvent, UndoEvent};
+use crate::resources::move_history::Move;
+use crate::{Board, BoardOptions};
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+/// Undoes and redoes moves. The chunks changed by a move are despawned,
+/// to be spawned again from the board state with their covers and flags
+pub fn history_event_handler(
+    mut commands: Commands,
+    mut boards: Query<&mut Board, Without<RemoteBoard>>,
+    board_options: Res<BoardOptions>,
+    mut undo_evr: EventReader<UndoEvent>,
+    mut redo_evr: EventReader<RedoEvent>,
+    mut board_completed_event_wr: EventWriter<BoardCompletedEvent>,
+    mut bomb_explosion_event_wr: EventWriter<BombExplosionEvent>,
+) {
+    let mut moves = Vec::new();
+    for event in undo_evr.read() {
+        let Ok(mut board) = boards.get_mut(event.board) else {
+            continue;
+        };
+        match board.undo(board_options.mode) {
+            None => log::debug!("Nothing to undo"),
+            Some(m) => moves.push((event.board, m)),
+        }
+    }
+    for event in redo_evr.read() {
+        let Ok(mut board) = boards.get_mut(event.board) else {
+            continue;
+        };
+        let Some(m) = board.redo() else {
+            log::debug!("Nothing to redo");
+            continue;
+        };
+        if let Move::Reveal { tiles, .. } | Move::Chord { tiles, .. } = &m {
+            if let Some(bomb) = tiles.iter().find(|c| board.tile_map.is_bomb_at(**c)) {
+                log::info!("Bomb !");
+                bomb_explosion_event_wr.send(BombExplosionEvent {
+                    board: event.board,
+                    coordinates: *bomb,
+                });
+            } else if board.is_completed() {
+                log::info!("Board completed");
+                board_completed_event_wr.send(BoardCompletedEvent { board: event.board });
+            }
+        }
+        moves.push((event.board, m));
+    }
+    let chunks: HashSet<_> = moves
+        .iter()
+        .flat_map(|(board, m)| m.tiles().into_iter().map(|c| (*board, Board::chunk_of(c))))
+        .collect();
+    for (board, chunk) in chunks {
+        let Ok(mut board) = boards.get_mut(board) else {
+            continue;
+        };
+        if let Some(entity) = board.remove_chunk(chunk) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::components::{Coordinates, SkinPart};
+use crate::resources::tile::Mark;
+use crate::resources::{BoardOptions, GameStatus};
+use crate::spawn::{spawn_skin_sprite, tile_center};
+use crate::systems::input::{board_at, cursor_world_position};
+use crate::{Board, BoardAssets};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy::window::PrimaryWindow;
+
+/// Look of a highlighted tile cover
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Highlight {
+    Hovered,
+    Pressed,
+}
+
+/// Highlighted tile covers of a board, at a tile size, with the overlays drawing them
+#[derive(Debug, Default)]
+pub struct Highlighted {
+    board: Option<(Entity, f32)>,
+    highlights: HashMap<Coordinates, Highlight>,
+    overlays: Vec<Entity>,
+}
+
+/// Highlights the covered tile under the cursor on any board, and shows the pressed look
+/// on the tiles a held left or middle button would uncover. Covers are batched in their
+/// chunk mesh, so the highlights are drawn as overlays above them
+#[allow(clippy::too_many_arguments)]
+pub fn highlight_tiles(
+    mut commands: Commands,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    boards: Query<&Board>,
+    statuses: Query<&GameStatus>,
+    board_assets: Res<BoardAssets>,
+    board_options: Res<BoardOptions>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut highlighted: Local<Highlighted>,
+) {
+    let hovered = cursor_world_position(windows.single(), &cameras)
+        .and_then(|pos| board_at(boards.iter(), pos));
+    let mut highlights = HashMap::new();
+    if let Some((board, hovered)) = hovered {
+        // Bombs of a lost board are shown
+        let lost = statuses.get(board.entity) == Ok(&GameStatus::Lost);
+        let covered = |c: &Coordinates| {
+            board.chunks.contains_key(&Board::chunk_of(*c))
+                && board.covered_tiles.contains(c)
+                && !(lost && board.tile_map.is_bomb_at(*c))
+        };
+        let chording = buttons.pressed(MouseButton::Middle)
+            || (buttons.pressed(MouseButton::Left) && !board.covered_tiles.contains(&hovered));
+        let pressed: Vec<Coordinates> = if chording {
+            board.tile_map.safe_square_at(hovered).chain([hovered]).collect()
+        } else if buttons.pressed(MouseButton::Left) {
+            vec![hovered]
+        } else {
+            Vec::new()
+        };
+        for coordinates in pressed {
+            if board.mark_at(&coordinates) == Some(Mark::Flag) || !covered(&coordinates) {
+                continue;
+            }
+            highlights.insert(coordinates, Highlight::Pressed);
+        }
+        if covered(&hovered) {
+            highlights.entry(hovered).or_insert(Highlight::Hovered);
+        }
+    }
+    let highlighted_board = hovered
+        .filter(|_| !highlights.is_empty())
+        .map(|(board, _)| (board.entity, board.tile_size));
+    if highlighted_board == highlighted.board && highlights == highlighted.highlights {
+        return;
+    }
+
+    // Replaces the overlays of the previous highlights, at most a chord square
+    for overlay in highlighted.overlays.drain(..) {
+        if let Some(overlay) = commands.get_entity(overlay) {
+            overlay.despawn_recursive();
+        }
+    }
+    if let Some((board, _)) = hovered.filter(|_| highlighted_board.is_some()) {
+        let size = board.tile_size - board_options.tile_padding;
+        let mut overlays = Vec::new();
+        commands.entity(board.entity).with_children(|parent| {
+            for (coordinates, highlight) in highlights.iter() {
+                let (part, color) = match highlight {
+                    // An uncovered empty tile looks pressed
+                    Highlight::Pressed => (SkinPart::Tile, None),
+                    Highlight::Hovered => (SkinPart::Cover, Some(Color::srgb(0.85, 0.9, 1.))),
+                };
+                let position = tile_center(*coordinates, board.tile_size).extend(3.5);
+                let mut cmd = spawn_skin_sprite(
+                    parent,
+                    &board_assets,
+                    part,
+                    size,
+                    Transform::from_translation(position),
+                );
+                cmd.insert(Name::new("Highlight"));
+                if let Some(color) = color {
+                    let skin_color = board_assets.skin_sprite(part).map_or(Color::WHITE, |s| s.color);
+                    cmd.insert(Sprite {
+                        color: skin_color.mix(&color, 0.35),
+                        custom_size: Some(Vec2::splat(size)),
+                        ..Default::default()
+                    });
+                }
+                overlays.push(cmd.id());
+            }
+        });
+        highlighted.overlays = overlays;
+    }
+    highlighted.board = highlighted_board;
+    highlighted.highlights = highlights;
+}
@@ -1,14 +1,42 @@
+use crate::components::{Coordinates, KeyBindings};
 use crate::events::TileTriggerEvent;
 use crate::Board;
+use crate::resources::KeyboardCursor;
 use crate::TileMarkEvent;
+use crate::events::{RedoEvent, UndoEvent};
 use bevy::input::{mouse::MouseButtonInput, ButtonState};
 use bevy::log;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
+/// Retrieves the world position of the cursor, through the active camera
+pub(crate) fn cursor_world_position(
+    window: &Window,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let (camera, camera_transform) = cameras.iter().find(|(c, _)| c.is_active)?;
+    window
+        .cursor_position()
+        .and_then(|p| camera.viewport_to_world_2d(camera_transform, p))
+}
+
+/// Retrieves the board under a world position, with the tile coordinates
+pub(crate) fn board_at<'a>(
+    boards: impl IntoIterator<Item = &'a Board>,
+    position: Vec2,
+) -> Option<(&'a Board, Coordinates)> {
+    boards
+        .into_iter()
+        .find_map(|board| Some((board, board.mouse_position(position)?)))
+}
+
+/// Marks tiles on right button press, and triggers them on left or middle button
+/// release, so a misclick is cancelled by dragging off the board
 pub fn input_handling (
     windows: Query<&Window, With<PrimaryWindow>>,
-    board: Res<Board>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    boards: Query<&Board>,
+    mut pressed: Local<Option<(Entity, MouseButton)>>,
     mut button_evr: EventReader<MouseButtonInput>,
     mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
     mut tile_mark_ewr: EventWriter<TileMarkEvent>,
@@ -16,25 +44,102 @@ pub fn input_handling (
     let window = windows.single();
 
     for event in button_evr.read(){
-        if let ButtonState::Pressed = event.state {
-            let position = window.cursor_position();
-            if let Some(pos) = position {
-                log::trace!("Mouse button pressed] {:?} at {}",event.button, pos);
-                let tile_coordinates = board.mouse_position(window, pos);
-                if let Some(coordinates) = tile_coordinates {
-                    match event.button {
-                        MouseButton::Left => {
-                            log::info!("Trying to uncover tile on {}", coordinates);
-                            tile_trigger_ewr.send(TileTriggerEvent(coordinates));
-                        }
-                        MouseButton::Right => {
-                            log::info!("Trying to mark tile on {}", coordinates);
-                            tile_mark_ewr.send(TileMarkEvent(coordinates));
-                        }
-                        _ => (),
+        let position = cursor_world_position(window, &cameras);
+        log::trace!("Mouse button {:?} {:?} at {:?}", event.button, event.state, position);
+        let tile = position
+            .and_then(|pos| board_at(boards.iter(), pos))
+            .map(|(board, coordinates)| (board.entity, coordinates));
+        match (event.button, event.state) {
+            (MouseButton::Left | MouseButton::Middle, ButtonState::Pressed) => {
+                if let Some((board, _)) = tile {
+                    *pressed = Some((board, event.button));
+                }
+            }
+            (MouseButton::Left | MouseButton::Middle, ButtonState::Released) => {
+                let Some((pressed_board, button)) = pressed.take() else {
+                    continue;
+                };
+                if button != event.button {
+                    continue;
+                }
+                // Releasing over another board cancels too
+                match tile {
+                    Some((board, coordinates)) if board == pressed_board => {
+                        log::info!("Trying to uncover tile on {}", coordinates);
+                        tile_trigger_ewr.send(TileTriggerEvent { board, coordinates });
                     }
+                    _ => log::debug!("Uncover cancelled"),
+                }
+            }
+            (MouseButton::Right, ButtonState::Pressed) => {
+                if let Some((board, coordinates)) = tile {
+                    log::info!("Trying to mark tile on {}", coordinates);
+                    tile_mark_ewr.send(TileMarkEvent { board, coordinates });
                 }
             }
+            _ => (),
         }
     }
 }
+
+/// The board key bindings move its keyboard cursor, uncover or chord the focused tile
+/// and mark it
+pub fn keyboard_input_handling(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut boards: Query<(&Board, &mut KeyboardCursor, &KeyBindings)>,
+    mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
+    mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+) {
+    for (board, mut cursor, bindings) in boards.iter_mut() {
+        let moves = [
+            (bindings.left, (-1, 0)),
+            (bindings.right, (1, 0)),
+            (bindings.up, (0, 1)),
+            (bindings.down, (0, -1)),
+        ];
+        for (key, (x, y)) in moves {
+            if keys.just_pressed(key) {
+                let coordinates = Coordinates {
+                    x: (cursor.coordinates.x + x).clamp(0, board.tile_map.width() as i32 - 1),
+                    y: (cursor.coordinates.y + y).clamp(0, board.tile_map.height() as i32 - 1),
+                };
+                if coordinates != cursor.coordinates {
+                    cursor.coordinates = coordinates;
+                }
+            }
+        }
+        let coordinates = cursor.coordinates;
+        if keys.any_just_pressed(bindings.reveal.iter().copied()) {
+            log::info!("Trying to uncover tile on {}", coordinates);
+            tile_trigger_ewr.send(TileTriggerEvent { board: board.entity, coordinates });
+        }
+        if keys.any_just_pressed(bindings.mark.iter().copied()) {
+            log::info!("Trying to mark tile on {}", coordinates);
+            tile_mark_ewr.send(TileMarkEvent { board: board.entity, coordinates });
+        }
+    }
+}
+
+/// Ctrl+Z undoes the last move, Ctrl+Y or Ctrl+Shift+Z plays it again.
+/// Only with a single board, so players can't undo the moves of each other
+pub fn undo_input_handling(
+    keys: Res<ButtonInput<KeyCode>>,
+    boards: Query<Entity, With<Board>>,
+    mut undo_ewr: EventWriter<UndoEvent>,
+    mut redo_ewr: EventWriter<RedoEvent>,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let Ok(board) = boards.get_single() else {
+        return;
+    };
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keys.just_pressed(KeyCode::KeyY) || (shift && keys.just_pressed(KeyCode::KeyZ)) {
+        log::info!("Redo");
+        redo_ewr.send(RedoEvent { board });
+    } else if keys.just_pressed(KeyCode::KeyZ) {
+        log::info!("Undo");
+        undo_ewr.send(UndoEvent { board });
+    }
+}
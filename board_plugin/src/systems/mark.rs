@@ -1,43 +1,75 @@
-use crate::{Board, BoardAssets, TileMarkEvent};
-use bevy::log;
+use crate::components::{RemoteBoard, SkinPart};
+use crate::resources::tile::Mark;
+use crate::resources::GameStatus;
+use crate::spawn::spawn_skin_sprite;
+use crate::events::TileMarkedEvent;
+use crate::{Board, BoardAssets, BoardOptions, TileMarkEvent};
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 
 pub fn mark_tiles(
     mut commands: Commands,
-    mut board: ResMut<Board>,
-    board_assets: Res<BoardAssets>,
+    mut boards: Query<(&mut Board, &GameStatus), Without<RemoteBoard>>,
+    board_options: Res<BoardOptions>,
     mut tile_mark_event_rdr: EventReader<TileMarkEvent>,
-    query: Query<&Children>,
+    mut tile_marked_ewr: EventWriter<TileMarkedEvent>,
 ) {
     for event in tile_mark_event_rdr.read() {
-        if let Some((entity, mark)) = board.try_toggle_mark(&event.0) {
-            if mark {
-                commands.entity(entity).with_children(|parent| {
-                    parent
-                        .spawn(SpriteBundle {
-                            texture: board_assets.flag_material.texture.clone(),
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::splat(board.tile_size)),
-                                color: board_assets.flag_material.color,
-                                ..Default::default()
-                            },
-                            transform: Transform::from_xyz(0., 0., 1.),
-                            ..Default::default()
-                        })
-                        .insert(Name::new("Flag"));
-                });
-            } else {
-                let children = match query.get(entity) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        log::error!("Failed to retrieve flag entity components: {}", e);
-                        continue;
-                    }
-                };
-                for child in children.iter() {
-                    commands.entity(*child).despawn_recursive();
-                }
+        let Ok((mut board, status)) = boards.get_mut(event.board) else {
+            continue;
+        };
+        if *status != GameStatus::Playing {
+            continue;
+        }
+        if let Some(mark) = board.toggle_mark(&event.coordinates, board_options.question_marks) {
+            tile_marked_ewr.send(TileMarkedEvent {
+                board: event.board,
+                coordinates: event.coordinates,
+                mark,
+            });
+            // The chunk is spawned again with the new mark
+            if let Some(entity) = board.remove_chunk(Board::chunk_of(event.coordinates)) {
+                commands.entity(entity).despawn_recursive();
             }
         }
     }
 }
+
+/// Spawns the sprite of a mark over a tile cover
+pub(crate) fn spawn_mark<'a>(
+    parent: &'a mut ChildBuilder,
+    board_assets: &BoardAssets,
+    mark: Mark,
+    size: f32,
+    transform: Transform,
+) -> EntityCommands<'a> {
+    let (part, name) = match mark {
+        Mark::Flag => (SkinPart::Flag, "Flag"),
+        Mark::Question => (SkinPart::Question, "Question Mark"),
+    };
+    let mut cmd = spawn_skin_sprite(parent, board_assets, part, size, transform);
+    cmd.insert(Name::new(name));
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Coordinates;
+    use crate::systems::uncover::tests::{large_board_app, spawned_chunks};
+
+    #[test]
+    fn mark_rebuilds_its_chunk_only() {
+        let (mut app, board) = large_board_app();
+        app.add_event::<TileMarkEvent>()
+            .add_event::<TileMarkedEvent>()
+            .add_systems(Update, mark_tiles);
+        for coordinates in [Coordinates { x: 300, y: 300 }, Coordinates { x: 301, y: 302 }] {
+            app.world_mut().send_event(TileMarkEvent { board, coordinates });
+        }
+        app.update();
+        assert_eq!(spawned_chunks(&mut app, board), (1023, 1023));
+        let board = app.world().get::<Board>(board).unwrap();
+        assert_eq!(board.marked_tiles.len(), 2);
+    }
+}
@@ -1,3 +1,15 @@
 pub mod input;
 pub mod uncover;
 pub mod mark;
+pub mod history;
+pub mod endless;
+pub mod theme;
+pub mod hover;
+pub mod animation;
+pub mod timer;
+pub mod sound;
+pub mod number_style;
+pub mod narration;
+pub mod status;
+pub mod pause;
+pub mod net;
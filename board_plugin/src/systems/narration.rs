@@ -0,0 +1,184 @@
+use crate::components::{Coordinates, Narrator, Player, TileCursor};
+use crate::events::*;
+use crate::resources::tile::{Mark, Tile};
+use crate::resources::{GameTimer, KeyboardCursor};
+use crate::Board;
+use bevy::a11y::accesskit::{Live, NodeBuilder, Role};
+use bevy::a11y::{AccessibilityNode, Focus};
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+/// Accessibility node announcing the narration, to spawn as a board child
+pub(crate) fn narrator_bundle() -> (Name, Narrator, SpatialBundle, AccessibilityNode) {
+    let mut node = NodeBuilder::new(Role::Status);
+    node.set_live(Live::Polite);
+    (
+        Name::new("Narrator"),
+        Narrator,
+        SpatialBundle::default(),
+        AccessibilityNode(node),
+    )
+}
+
+/// Keyboard cursor outline, to spawn as a board child
+pub(crate) fn cursor_bundle() -> (Name, TileCursor, SpriteBundle) {
+    (
+        Name::new("Keyboard Cursor"),
+        TileCursor,
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(1., 0.85, 0., 0.4),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+}
+
+/// Describes a tile, for example "Row 3, column 5, revealed, 2 adjacent mines, 1 flagged neighbor".
+/// Rows are counted from the top of the board
+pub fn describe_tile(board: &Board, coordinates: Coordinates) -> String {
+    let row = board.tile_map.height() as i32 - coordinates.y;
+    let column = coordinates.x + 1;
+    let state = match board.mark_at(&coordinates) {
+        _ if !board.covered_tiles.contains(&coordinates) => {
+            match board.tile_map.tile_at(coordinates) {
+                Some(Tile::Bomb) => "revealed, mine".to_string(),
+                Some(Tile::BombNeighbor(1)) => "revealed, 1 adjacent mine".to_string(),
+                Some(Tile::BombNeighbor(count)) => format!("revealed, {} adjacent mines", count),
+                _ => "revealed, no adjacent mine".to_string(),
+            }
+        }
+        Some(Mark::Flag) => "flagged".to_string(),
+        Some(Mark::Question) => "question marked".to_string(),
+        None => "covered".to_string(),
+    };
+    let flagged = board
+        .tile_map
+        .safe_square_at(coordinates)
+        .filter(|c| board.mark_at(c) == Some(Mark::Flag))
+        .count();
+    match flagged {
+        0 => format!("Row {}, column {}, {}", row, column, state),
+        1 => format!("Row {}, column {}, {}, 1 flagged neighbor", row, column, state),
+        n => format!("Row {}, column {}, {}, {} flagged neighbors", row, column, state, n),
+    }
+}
+
+/// Describes the game state, for example "30 mines left, 120 tiles covered, 42 seconds"
+pub fn describe_game(board: &Board, timer: &GameTimer) -> String {
+    let mines_left = board.tile_map.bomb_count() as i64 - board.marked_tiles.len() as i64;
+    format!(
+        "{} mines left, {} tiles covered, {} seconds",
+        mines_left,
+        board.covered_tiles.len(),
+        timer.seconds(),
+    )
+}
+
+/// Narrates the focused tile of each board when its keyboard cursor moves, the outcome
+/// of moves, and the game state when I is pressed. With several boards, the narration
+/// starts with the player name
+#[allow(clippy::too_many_arguments)]
+pub fn narrate(
+    boards: Query<(Entity, &Board, Ref<KeyboardCursor>, &GameTimer, &Player)>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut tiles_uncovered_evr: EventReader<TilesUncoveredEvent>,
+    mut tile_marked_evr: EventReader<TileMarkedEvent>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    mut narration_ewr: EventWriter<NarrationEvent>,
+) {
+    let mut sentences: HashMap<Entity, Vec<String>> = HashMap::new();
+    for event in tiles_uncovered_evr.read() {
+        if event.count > 1 {
+            sentences
+                .entry(event.board)
+                .or_default()
+                .push(format!("{} tiles revealed", event.count));
+        }
+    }
+    for event in bomb_explosion_evr.read() {
+        if let Ok((_, board, ..)) = boards.get(event.board) {
+            sentences.entry(event.board).or_default().push(format!(
+                "Mine exploded at {}, game over",
+                describe_position(board, event.coordinates)
+            ));
+        }
+    }
+    for event in board_completed_evr.read() {
+        if let Ok((_, _, _, timer, _)) = boards.get(event.board) {
+            sentences
+                .entry(event.board)
+                .or_default()
+                .push(format!("Board cleared in {} seconds", timer.seconds()));
+        }
+    }
+    let marked: HashSet<Entity> = tile_marked_evr.read().map(|e| e.board).collect();
+    let several = boards.iter().count() > 1;
+    for (entity, board, cursor, timer, player) in boards.iter() {
+        let mut sentences = sentences.remove(&entity).unwrap_or_default();
+        if cursor.is_changed() || marked.contains(&entity) || !sentences.is_empty() {
+            sentences.push(describe_tile(board, cursor.coordinates));
+        }
+        if keys.just_pressed(KeyCode::KeyI) {
+            sentences.push(describe_game(board, timer));
+        }
+        if sentences.is_empty() {
+            continue;
+        }
+        let text = sentences.join(". ");
+        narration_ewr.send(NarrationEvent {
+            board: entity,
+            text: match several {
+                true => format!("{}: {}", player.name, text),
+                false => text,
+            },
+        });
+    }
+}
+
+fn describe_position(board: &Board, coordinates: Coordinates) -> String {
+    let row = board.tile_map.height() as i32 - coordinates.y;
+    format!("row {}, column {}", row, coordinates.x + 1)
+}
+
+/// Announces the narration of each board through its accessibility node
+pub fn announce(
+    mut narration_evr: EventReader<NarrationEvent>,
+    mut narrators: Query<(Entity, &mut AccessibilityNode, &Parent), With<Narrator>>,
+    mut focus: Option<ResMut<Focus>>,
+) {
+    for narration in narration_evr.read() {
+        log::debug!("Narration: {}", narration.text);
+        let narrator = narrators
+            .iter_mut()
+            .find(|(_, _, parent)| parent.get() == narration.board);
+        let Some((entity, mut node, _)) = narrator else {
+            continue;
+        };
+        node.0.set_name(narration.text.clone());
+        // Screen readers follow the focus, left to the UI when it has one
+        if let Some(focus) = focus.as_mut() {
+            if focus.0.is_none() {
+                focus.0 = Some(entity);
+            }
+        }
+    }
+}
+
+/// Moves the keyboard cursor outline of each board over its focused tile
+pub fn draw_cursor(
+    boards: Query<(&Board, &KeyboardCursor, &Children)>,
+    mut outlines: Query<(&mut Transform, &mut Sprite), With<TileCursor>>,
+) {
+    for (board, cursor, children) in boards.iter() {
+        let mut outlines = outlines.iter_many_mut(children);
+        while let Some((mut transform, mut sprite)) = outlines.fetch_next() {
+            let position = Vec2::new(cursor.coordinates.x as f32 + 0.5, cursor.coordinates.y as f32 + 0.5);
+            transform.translation = (position * board.tile_size).extend(5.);
+            sprite.custom_size = Some(Vec2::splat(board.tile_size));
+        }
+    }
+}
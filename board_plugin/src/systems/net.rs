@@ -0,0 +1,140 @@
+use crate::components::{Coordinates, RemoteBoard};
+use crate::events::{
+    BoardCompletedEvent, BombExplosionEvent, TileMarkEvent, TileMarkedEvent, TileTriggerEvent,
+    TilesUncoveredEvent,
+};
+use crate::net::{ClientMessage, NetClient, ServerMessage};
+use crate::resources::{GameStatus, KeyboardCursor};
+use crate::systems::input::cursor_world_position;
+use crate::Board;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy::window::PrimaryWindow;
+
+/// Sends the moves played on the remote board to the server
+pub fn send_moves(
+    mut client: ResMut<NetClient>,
+    boards: Query<&GameStatus, With<RemoteBoard>>,
+    mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut tile_mark_evr: EventReader<TileMarkEvent>,
+) {
+    let playing = |board| boards.get(board).is_ok_and(|s| *s == GameStatus::Playing);
+    let triggers: Vec<_> = tile_trigger_evr
+        .read()
+        .filter(|e| playing(e.board))
+        .map(|e| ClientMessage::Trigger(e.coordinates))
+        .collect();
+    let marks: Vec<_> = tile_mark_evr
+        .read()
+        .filter(|e| playing(e.board))
+        .map(|e| ClientMessage::Mark(e.coordinates))
+        .collect();
+    for message in triggers.iter().chain(marks.iter()) {
+        client.send(message);
+    }
+}
+
+/// Sends the tile under the mouse, or else under the keyboard cursor, to be shown to the spectators
+pub fn send_cursor(
+    mut client: ResMut<NetClient>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    boards: Query<(&Board, &KeyboardCursor), With<RemoteBoard>>,
+    mut last: Local<Option<Coordinates>>,
+) {
+    if client.spectator {
+        return;
+    }
+    let Ok((board, keyboard)) = boards.get_single() else {
+        return;
+    };
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(|window| cursor_world_position(window, &cameras))
+        .and_then(|position| board.mouse_position(position))
+        .unwrap_or(keyboard.coordinates);
+    if *last != Some(cursor) {
+        *last = Some(cursor);
+        client.send(&ClientMessage::Cursor(cursor));
+    }
+}
+
+/// Applies the moves played by the server to the remote board. The chunks changed by a move
+/// are despawned, to be spawned again from the board state
+#[allow(clippy::too_many_arguments)]
+pub fn receive_messages(
+    mut commands: Commands,
+    mut client: ResMut<NetClient>,
+    mut boards: Query<(&mut Board, &RemoteBoard, &mut KeyboardCursor)>,
+    mut tiles_uncovered_ewr: EventWriter<TilesUncoveredEvent>,
+    mut tile_marked_ewr: EventWriter<TileMarkedEvent>,
+    mut bomb_explosion_ewr: EventWriter<BombExplosionEvent>,
+    mut board_completed_ewr: EventWriter<BoardCompletedEvent>,
+) {
+    client.receive();
+    let Some((round, watched)) = client.welcome.as_ref().map(|w| (w.round, w.player)) else {
+        return;
+    };
+    // Messages of a new round wait for its board
+    let board = boards.iter_mut().find(|(_, remote, _)| remote.round == round);
+    let Some((mut board, _, mut keyboard)) = board else {
+        return;
+    };
+    if let Some(coordinates) = client.exploded.take() {
+        log::info!("Bomb !");
+        bomb_explosion_ewr.send(BombExplosionEvent {
+            board: board.entity,
+            coordinates,
+        });
+    }
+    let mut changed = HashSet::new();
+    while let Some(message) = client.pending.pop_front() {
+        match message {
+            ServerMessage::Uncovered { tiles, origin, chord } => {
+                board.apply_uncovered(&tiles);
+                changed.extend(tiles.iter().map(|(c, _)| Board::chunk_of(*c)));
+                if let Some(origin) = origin {
+                    tiles_uncovered_ewr.send(TilesUncoveredEvent {
+                        board: board.entity,
+                        origin,
+                        count: tiles.len(),
+                        chord,
+                    });
+                }
+            }
+            ServerMessage::Marked { coordinates, mark } => {
+                board.apply_mark(&coordinates, mark);
+                changed.insert(Board::chunk_of(coordinates));
+                tile_marked_ewr.send(TileMarkedEvent {
+                    board: board.entity,
+                    coordinates,
+                    mark,
+                });
+            }
+            // The explosion is announced once the bombs are spawned
+            ServerMessage::Exploded { coordinates, bombs } => {
+                board.apply_bombs(&bombs);
+                changed.extend(bombs.iter().map(|c| Board::chunk_of(*c)));
+                client.exploded = Some(coordinates);
+            }
+            ServerMessage::Completed => {
+                log::info!("Board completed");
+                board_completed_ewr.send(BoardCompletedEvent { board: board.entity });
+            }
+            // Spectators follow the cursor of the watched player
+            ServerMessage::Cursor { player, coordinates } => {
+                if client.spectator && player == watched {
+                    keyboard.coordinates = coordinates;
+                }
+            }
+            message => log::debug!("Ignored {:?}", message),
+        }
+    }
+    for chunk in changed {
+        if let Some(entity) = board.remove_chunk(chunk) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
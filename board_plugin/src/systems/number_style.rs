@@ -0,0 +1,101 @@
+use crate::components::{NumberGlyph, SkinPart};
+use crate::resources::NumberStyle;
+use crate::BoardAssets;
+use bevy::prelude::*;
+
+/// Pip positions of the number glyphs on a 3x3 grid, from 1 bomb up to 8
+const GLYPH_PIPS: [&[(i8, i8)]; 8] = [
+    &[(0, 0)],
+    &[(-1, 1), (1, -1)],
+    &[(-1, 1), (0, 0), (1, -1)],
+    &[(-1, 1), (1, 1), (-1, -1), (1, -1)],
+    &[(-1, 1), (1, 1), (0, 0), (-1, -1), (1, -1)],
+    &[(-1, 1), (1, 1), (-1, 0), (1, 0), (-1, -1), (1, -1)],
+    &[(-1, 1), (1, 1), (-1, 0), (0, 0), (1, 0), (-1, -1), (1, -1)],
+    &[(-1, 1), (0, 1), (1, 1), (-1, 0), (1, 0), (-1, -1), (0, -1), (1, -1)],
+];
+
+/// Number glyph transform on a bomb counter of the given size, in the lower right corner
+fn glyph_transform(size: f32) -> Transform {
+    Transform::from_xyz(size * 0.32, -size * 0.32, 1.).with_scale(Vec3::new(size, size, 1.))
+}
+
+/// Bomb counters, drawn as text or atlas sprite, with their glyph
+type Counters<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        Ref<'static, SkinPart>,
+        Option<&'static mut Text>,
+        Option<Ref<'static, Sprite>>,
+        Option<&'static Children>,
+    ),
+>;
+
+/// Applies the `NumberStyle` palette and glyphs to bomb counters, on spawn,
+/// on resize and whenever the style or the assets change
+pub fn style_numbers(
+    mut commands: Commands,
+    number_style: Res<NumberStyle>,
+    board_assets: Res<BoardAssets>,
+    mut counters: Counters,
+    glyphs: Query<Entity, With<NumberGlyph>>,
+) {
+    let restyle_all = number_style.is_changed() || board_assets.is_changed();
+    for (entity, part, text, sprite, children) in counters.iter_mut() {
+        let SkinPart::BombCounter(count) = *part else {
+            continue;
+        };
+        let resized = text.as_ref().is_some_and(|t| t.is_changed())
+            || sprite.as_ref().is_some_and(|s| s.is_changed());
+        if !restyle_all && !resized && !part.is_added() {
+            continue;
+        }
+        let color = match number_style.palette {
+            Some(palette) => palette.color(count),
+            None => board_assets.bomb_counter_colors(count),
+        };
+        let size = match (&text, sprite) {
+            (Some(text), _) => text.sections.first().map_or(0., |s| s.style.font_size),
+            (None, Some(sprite)) => sprite.custom_size.unwrap_or_default().x,
+            (None, None) => continue,
+        };
+        // Atlas counters keep the atlas colors
+        if let Some(mut text) = text {
+            if text.sections.iter().any(|s| s.style.color != color) {
+                for section in text.sections.iter_mut() {
+                    section.style.color = color;
+                }
+            }
+        }
+        for glyph in children.into_iter().flatten().filter(|c| glyphs.contains(**c)) {
+            commands.entity(*glyph).despawn_recursive();
+        }
+        if number_style.glyphs {
+            commands.entity(entity).with_children(|parent| {
+                spawn_glyph(parent, count, color, size);
+            });
+        }
+    }
+}
+
+fn spawn_glyph(parent: &mut ChildBuilder, count: u8, color: Color, size: f32) {
+    parent
+        .spawn(SpatialBundle::from_transform(glyph_transform(size)))
+        .insert(Name::new("Number Glyph"))
+        .insert(NumberGlyph)
+        .with_children(|parent| {
+            for (x, y) in GLYPH_PIPS[(count.clamp(1, 8) - 1) as usize] {
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::splat(0.07)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(*x as f32 * 0.09, *y as f32 * 0.09, 0.),
+                    ..Default::default()
+                });
+            }
+        });
+}
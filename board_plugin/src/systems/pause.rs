@@ -0,0 +1,144 @@
+use bevy::input::mouse::MouseButtonInput;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+
+use crate::components::PauseOverlay;
+use crate::resources::board::Board;
+use crate::resources::BoardAssets;
+use crate::states::BoardPause;
+
+/// The Pause key toggles the pause, a click on the pause overlay resumes
+pub fn pause_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    pause: Res<State<BoardPause>>,
+    mut next_pause: ResMut<NextState<BoardPause>>,
+) {
+    let toggle = keys.just_pressed(KeyCode::Pause);
+    match pause.get() {
+        BoardPause::Running if toggle => next_pause.set(BoardPause::Paused),
+        BoardPause::Paused if toggle || buttons.just_released(MouseButton::Left) => {
+            next_pause.set(BoardPause::Running)
+        }
+        _ => (),
+    }
+}
+
+/// Drops the mouse input of the click resuming the game, which the board input
+/// would otherwise read once running again
+pub fn consume_resume_click(
+    mut button_events: ResMut<Events<MouseButtonInput>>,
+    mut buttons: ResMut<ButtonInput<MouseButton>>,
+) {
+    button_events.clear();
+    buttons.reset_all();
+}
+
+/// Pauses the board when the window loses the focus
+pub fn auto_pause(
+    mut focus_evr: EventReader<WindowFocused>,
+    mut next_pause: ResMut<NextState<BoardPause>>,
+) {
+    if focus_evr.read().any(|e| !e.focused) {
+        log::info!("window lost focus, pausing");
+        next_pause.set(BoardPause::Paused);
+    }
+}
+
+/// Hides the boards behind the pause overlay
+pub fn show_pause_overlay(
+    mut commands: Commands,
+    board_assets: Res<BoardAssets>,
+    mut boards: Query<&mut Visibility, With<Board>>,
+) {
+    for mut visibility in boards.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            background_color: Color::srgb(0.1, 0.1, 0.12).into(),
+            z_index: ZIndex::Global(5),
+            ..Default::default()
+        })
+        .insert(Name::new("Pause Overlay"))
+        .insert(PauseOverlay)
+        .with_children(|parent| {
+            for (value, font_size) in [("Paused", 32.), ("Click or press Pause to resume", 12.)] {
+                parent.spawn(TextBundle::from_section(
+                    value,
+                    TextStyle {
+                        font: board_assets.bomb_counter_font.clone(),
+                        font_size,
+                        color: Color::WHITE,
+                    },
+                ));
+            }
+        });
+}
+
+/// Removes the pause overlay and shows the boards again
+pub fn hide_pause_overlay(
+    mut commands: Commands,
+    overlays: Query<Entity, With<PauseOverlay>>,
+    mut boards: Query<&mut Visibility, With<Board>>,
+) {
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for mut visibility in boards.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::ButtonState;
+    use crate::states::BoardState;
+    use bevy::state::app::StatesPlugin;
+
+    #[test]
+    fn resume_click_does_not_reach_the_board() {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .add_event::<MouseButtonInput>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .insert_state(BoardState::Spawned)
+            .add_sub_state::<BoardPause>()
+            .add_systems(Update, pause_input)
+            .add_systems(OnExit(BoardPause::Paused), consume_resume_click);
+        app.update();
+        app.world_mut().resource_mut::<NextState<BoardPause>>().set(BoardPause::Paused);
+        app.update();
+
+        let window = app.world_mut().spawn_empty().id();
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            app.world_mut().send_event(MouseButtonInput {
+                button: MouseButton::Left,
+                state,
+                window,
+            });
+        }
+        let mut buttons = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+        buttons.press(MouseButton::Left);
+        buttons.release(MouseButton::Left);
+        app.update();
+        let next = app.world().resource::<NextState<BoardPause>>();
+        assert!(matches!(next, NextState::Pending(BoardPause::Running)));
+
+        app.update();
+        assert_eq!(app.world().resource::<State<BoardPause>>().get(), &BoardPause::Running);
+        assert!(app.world().resource::<Events<MouseButtonInput>>().is_empty());
+        assert!(!app.world().resource::<ButtonInput<MouseButton>>().just_released(MouseButton::Left));
+    }
+}
@@ -0,0 +1,108 @@
+use crate::events::*;
+use crate::resources::{BoardSound, SoundOptions};
+use crate::BoardAssets;
+use bevy::audio::Volume;
+use bevy::log;
+use bevy::prelude::*;
+
+/// Translates the board events to sound effects
+pub fn board_sounds(
+    mut tiles_uncovered_evr: EventReader<TilesUncoveredEvent>,
+    mut tile_marked_evr: EventReader<TileMarkedEvent>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    mut timer_tick_evr: EventReader<TimerTickEvent>,
+    mut sound_ewr: EventWriter<BoardSoundEvent>,
+) {
+    for event in tiles_uncovered_evr.read() {
+        sound_ewr.send(BoardSoundEvent(BoardSound::of_uncovered(event.count, event.chord)));
+    }
+    for event in tile_marked_evr.read() {
+        sound_ewr.send(BoardSoundEvent(BoardSound::of_mark(event.mark)));
+    }
+    for _ in bomb_explosion_evr.read() {
+        sound_ewr.send(BoardSoundEvent(BoardSound::Explosion));
+    }
+    for _ in board_completed_evr.read() {
+        sound_ewr.send(BoardSoundEvent(BoardSound::Win));
+    }
+    for _ in timer_tick_evr.read() {
+        sound_ewr.send(BoardSoundEvent(BoardSound::Tick));
+    }
+}
+
+/// Plays the sound effects. Only runs with the bevy audio plugin, which
+/// stays silent when no audio device exists
+pub fn play_sounds(
+    mut commands: Commands,
+    board_assets: Res<BoardAssets>,
+    sound_options: Res<SoundOptions>,
+    mut sound_evr: EventReader<BoardSoundEvent>,
+) {
+    for event in sound_evr.read() {
+        let Some(volume) = sound_options.playback_volume() else {
+            continue;
+        };
+        let Some(source) = board_assets.sounds.get(event.0) else {
+            log::trace!("No sound for {:?}", event.0);
+            continue;
+        };
+        commands.spawn(AudioBundle {
+            source: source.clone(),
+            settings: PlaybackSettings::DESPAWN
+                .with_volume(Volume::new(volume)),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Coordinates;
+    use crate::resources::tile::Mark;
+
+    /// Sounds of the board events of a frame, without the audio plugin
+    fn sounds(send: impl FnOnce(&mut World)) -> Vec<BoardSound> {
+        let mut app = App::new();
+        app.add_event::<TilesUncoveredEvent>()
+            .add_event::<TileMarkedEvent>()
+            .add_event::<BombExplosionEvent>()
+            .add_event::<BoardCompletedEvent>()
+            .add_event::<TimerTickEvent>()
+            .add_event::<BoardSoundEvent>()
+            .add_systems(Update, board_sounds);
+        send(app.world_mut());
+        app.update();
+        let events = app.world().resource::<Events<BoardSoundEvent>>();
+        events.get_reader().read(events).map(|e| e.0).collect()
+    }
+
+    #[test]
+    fn board_events_sounds() {
+        let board = Entity::PLACEHOLDER;
+        let origin = Coordinates::default();
+        let sounds = sounds(|world| {
+            world.send_event(TilesUncoveredEvent { board, origin, count: 1, chord: false });
+            world.send_event(TilesUncoveredEvent { board, origin, count: 9, chord: false });
+            world.send_event(TilesUncoveredEvent { board, origin, count: 9, chord: true });
+            world.send_event(TileMarkedEvent { board, coordinates: origin, mark: Some(Mark::Flag) });
+            world.send_event(TileMarkedEvent { board, coordinates: origin, mark: None });
+            world.send_event(BombExplosionEvent { board, coordinates: origin });
+            world.send_event(BoardCompletedEvent { board });
+            world.send_event(TimerTickEvent { board, seconds: 1 });
+        });
+        assert_eq!(
+            sounds,
+            [
+                BoardSound::Reveal,
+                BoardSound::Cascade,
+                BoardSound::Chord,
+                BoardSound::Flag,
+                BoardSound::Unflag,
+                BoardSound::Explosion,
+                BoardSound::Win,
+                BoardSound::Tick,
+            ]
+        );
+    }
+}
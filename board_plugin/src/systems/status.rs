@@ -0,0 +1,21 @@
+use crate::resources::GameStatus;
+use crate::Board;
+use bevy::log;
+use bevy::prelude::*;
+
+/// Updates the game status of the boards from their state whenever it changes
+pub fn update_status(mut boards: Query<(&Board, &mut GameStatus), Changed<Board>>) {
+    for (board, mut status) in boards.iter_mut() {
+        let current = if board.is_exploded() {
+            GameStatus::Lost
+        } else if board.is_completed() {
+            GameStatus::Won
+        } else {
+            GameStatus::Playing
+        };
+        if *status != current {
+            log::info!("Game status of {:?}: {:?}", board.entity, current);
+            *status = current;
+        }
+    }
+}
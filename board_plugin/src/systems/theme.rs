@@ -0,0 +1,121 @@
+use crate::components::SkinPart;
+use crate::resources::{BoardAssets, BoardOptions, BoardTheme, SkinSprite, Theme};
+use crate::spawn::spawn_bomb_counter;
+use bevy::ecs::system::EntityCommands;
+use bevy::log;
+use bevy::prelude::*;
+
+/// Sprites of the board entities, restyled on skin changes
+pub(crate) type SkinSprites<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static SkinPart,
+        &'static mut Sprite,
+        &'static mut Handle<Image>,
+        Option<&'static mut TextureAtlas>,
+        Option<&'static Parent>,
+    ),
+>;
+
+/// Applies the board theme once loaded, whenever it is switched or modified,
+/// restyling every spawned board entity
+#[allow(clippy::too_many_arguments)]
+pub fn apply_theme(
+    mut commands: Commands,
+    board_theme: Option<Res<BoardTheme>>,
+    themes: Res<Assets<Theme>>,
+    mut theme_evr: EventReader<AssetEvent<Theme>>,
+    board_options: Option<ResMut<BoardOptions>>,
+    mut sprites: SkinSprites,
+    mut texts: Query<(Entity, &SkinPart, &mut Text, Option<&Parent>)>,
+    transforms: Query<&Transform>,
+) {
+    let Some(board_theme) = board_theme else {
+        return;
+    };
+    let id = board_theme.0.id();
+    let reloaded = theme_evr
+        .read()
+        .any(|e| e.is_loaded_with_dependencies(id) || e.is_modified(id));
+    if !reloaded && !board_theme.is_changed() {
+        return;
+    }
+    // Not loaded yet, applied on its loaded event
+    let Some(theme) = themes.get(id) else {
+        return;
+    };
+    let assets = &theme.assets;
+    log::info!("Applying theme {}", assets.label);
+
+    for (entity, part, mut sprite, mut texture, atlas, parent) in sprites.iter_mut() {
+        match assets.skin_sprite(*part) {
+            Some(skin) => restyle_sprite(
+                commands.entity(entity),
+                skin,
+                &mut sprite,
+                &mut texture,
+                atlas,
+            ),
+            // Atlas bomb counter, now drawn as text
+            None => {
+                if let (SkinPart::BombCounter(count), Some(parent)) = (part, parent) {
+                    let size = sprite.custom_size.unwrap_or_default().x;
+                    let transform = transforms.get(entity).copied().unwrap_or_default();
+                    commands.entity(entity).despawn_recursive();
+                    commands.entity(parent.get()).with_children(|parent| {
+                        spawn_bomb_counter(parent, assets, *count, size, transform);
+                    });
+                }
+            }
+        }
+    }
+    for (entity, part, mut text, parent) in texts.iter_mut() {
+        let SkinPart::BombCounter(count) = part else {
+            continue;
+        };
+        // Text bomb counter, now drawn from the atlas
+        if assets.skin_sprite(*part).is_some() {
+            if let Some(parent) = parent {
+                let size = text.sections.first().map_or(0., |s| s.style.font_size);
+                let transform = transforms.get(entity).copied().unwrap_or_default();
+                commands.entity(entity).despawn_recursive();
+                commands.entity(parent.get()).with_children(|parent| {
+                    spawn_bomb_counter(parent, assets, *count, size, transform);
+                });
+            }
+            continue;
+        }
+        for section in text.sections.iter_mut() {
+            section.style.color = assets.bomb_counter_colors(*count);
+            section.style.font = assets.bomb_counter_font.clone();
+        }
+    }
+    if let (Some(mut options), Some(padding)) = (board_options, theme.tile_padding) {
+        options.tile_padding = padding;
+    }
+    commands.insert_resource::<BoardAssets>(assets.clone());
+}
+
+/// Applies a skin sprite to a spawned sprite, adding or removing its atlas
+pub(crate) fn restyle_sprite(
+    mut entity: EntityCommands,
+    skin: SkinSprite,
+    sprite: &mut Sprite,
+    texture: &mut Handle<Image>,
+    atlas: Option<Mut<TextureAtlas>>,
+) {
+    sprite.color = skin.color;
+    *texture = skin.texture;
+    match (skin.atlas, atlas) {
+        (Some(skin_atlas), Some(mut atlas)) => *atlas = skin_atlas,
+        (Some(skin_atlas), None) => {
+            entity.insert(skin_atlas);
+        }
+        (None, Some(_)) => {
+            entity.remove::<TextureAtlas>();
+        }
+        (None, None) => (),
+    }
+}
@@ -0,0 +1,96 @@
+use crate::events::{BoardCompletedEvent, BombExplosionEvent, TilesUncoveredEvent, TimerTickEvent};
+use crate::resources::{BoardOptions, GameStatus, GameTimer};
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Runs the game timer of each board from its first uncovered tiles until its game ends,
+/// and again once an undo brings a started game back to playing, sending a tick every
+/// second. Untimed game modes keep the timer stopped
+#[allow(clippy::too_many_arguments)]
+pub fn update_timer(
+    mut timers: Query<(Entity, &mut GameTimer)>,
+    statuses: Query<(Entity, Ref<GameStatus>)>,
+    board_options: Option<Res<BoardOptions>>,
+    time: Res<Time>,
+    mut tiles_uncovered_evr: EventReader<TilesUncoveredEvent>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    mut timer_tick_ewr: EventWriter<TimerTickEvent>,
+) {
+    let timed = board_options.is_none_or(|o| o.mode.is_timed());
+    for event in tiles_uncovered_evr.read().filter(|_| timed) {
+        if let Ok((_, mut timer)) = timers.get_mut(event.board) {
+            timer.running = true;
+        }
+    }
+    let ended = bomb_explosion_evr
+        .read()
+        .map(|e| e.board)
+        .chain(board_completed_evr.read().map(|e| e.board));
+    let resumed = statuses
+        .iter()
+        .filter(|(_, status)| timed && status.is_changed() && **status == GameStatus::Playing);
+    for (board, _) in resumed {
+        if let Ok((_, mut timer)) = timers.get_mut(board) {
+            timer.running |= timer.elapsed > Duration::ZERO;
+        }
+    }
+    for board in ended {
+        if let Ok((_, mut timer)) = timers.get_mut(board) {
+            timer.running = false;
+        }
+    }
+    for (board, mut timer) in timers.iter_mut() {
+        if !timer.running {
+            continue;
+        }
+        let seconds = timer.seconds();
+        timer.elapsed += time.delta();
+        if timer.seconds() > seconds {
+            timer_tick_ewr.send(TimerTickEvent {
+                board,
+                seconds: timer.seconds(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timer_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<TilesUncoveredEvent>()
+            .add_event::<BombExplosionEvent>()
+            .add_event::<BoardCompletedEvent>()
+            .add_event::<TimerTickEvent>()
+            .add_systems(Update, update_timer);
+        app
+    }
+
+    #[test]
+    fn timer_resumes_when_a_loss_is_undone() {
+        let mut app = timer_app();
+        let timer = GameTimer {
+            elapsed: Duration::from_secs(12),
+            running: false,
+        };
+        let board = app.world_mut().spawn((timer, GameStatus::Lost)).id();
+        app.update();
+        assert!(!app.world().get::<GameTimer>(board).unwrap().running);
+
+        *app.world_mut().get_mut::<GameStatus>(board).unwrap() = GameStatus::Playing;
+        app.update();
+        assert!(app.world().get::<GameTimer>(board).unwrap().running);
+    }
+
+    #[test]
+    fn timer_waits_for_the_first_uncovered_tiles() {
+        let mut app = timer_app();
+        let board = app.world_mut().spawn((GameTimer::default(), GameStatus::Playing)).id();
+        app.update();
+        assert!(!app.world().get::<GameTimer>(board).unwrap().running);
+    }
+}
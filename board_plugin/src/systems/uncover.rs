@@ -1,63 +1,180 @@
 use bevy::prelude::*;
 use bevy::log;
+use bevy::utils::HashSet;
+use crate::components::{RemoteBoard, Uncover};
+use crate::resources::GameStatus;
+use crate::systems::animation::{remove_cover, spawn_fading_cover, MAX_ANIMATED_COVERS};
+use crate::BoardAssets;
 use crate::BoardCompletedEvent;
 use crate::BombExplosionEvent;
-use crate::{Board, Bomb, BombNeighbor, Coordinates};
-use crate::events::TileTriggerEvent;
-
-/// Uncover component, indicates a covered tile that should be uncovered
-// #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
-#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
-pub struct Uncover;
+use crate::{Board, BoardOptions};
+use crate::events::{TileTriggerEvent, TilesUncoveredEvent};
 
+/// Uncovers the triggered tile and its whole opening in a single pass.
+/// Remote boards are played by the server
+#[allow(clippy::too_many_arguments)]
 pub fn trigger_event_handler(
     mut commands: Commands,
-    board: Res<Board>,
+    mut boards: Query<(&mut Board, &GameStatus), Without<RemoteBoard>>,
+    board_options: Res<BoardOptions>,
+    board_assets: Res<BoardAssets>,
     mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+    mut board_completed_event_wr: EventWriter<BoardCompletedEvent>,
+    mut bomb_explosion_event_wr: EventWriter<BombExplosionEvent>,
+    mut tiles_uncovered_ewr: EventWriter<TilesUncoveredEvent>,
 ) {
     for trigger_event in tile_trigger_evr.read() {
-        log::info!("tile trigger {}", &trigger_event.0);
-        if let Some(entity) = board.tile_to_uncover(&trigger_event.0) {
-            commands.entity(*entity).insert(Uncover);
+        let coords = trigger_event.coordinates;
+        log::info!("tile trigger {}", coords);
+        let Ok((mut board, status)) = boards.get_mut(trigger_event.board) else {
+            continue;
+        };
+        if *status != GameStatus::Playing || board.marked_tiles.contains(&coords) {
+            continue;
+        }
+        // Triggering an uncovered tile chords it
+        let chord = !board.covered_tiles.contains(&coords);
+        let opening = if chord {
+            board.chord(coords)
+        } else {
+            board.reveal(coords)
+        };
+        if opening.is_empty() {
+            log::debug!("Nothing to uncover from {}", coords);
+            continue;
+        }
+        tiles_uncovered_ewr.send(TilesUncoveredEvent {
+            board: board.entity,
+            origin: coords,
+            count: opening.len(),
+            chord,
+        });
+        log::debug!("Uncovered {} tiles from {}", opening.len(), coords);
+        let bomb = opening
+            .iter()
+            .map(|(c, _)| *c)
+            .find(|c| board.tile_map.is_bomb_at(*c));
+        let animated = opening.len() <= MAX_ANIMATED_COVERS;
+        let mut chunks = HashSet::new();
+        for (coords, ring) in opening {
+            let chunk = Board::chunk_of(coords);
+            if !board.chunks.contains_key(&chunk) {
+                continue;
+            }
+            chunks.insert(chunk);
+            if animated {
+                let delay = board_options
+                    .cascade_animation
+                    .as_ref()
+                    .map_or(0., |animation| animation.ring_delay * ring as f32);
+                spawn_fading_cover(&mut commands, &board, &board_assets, (coords, delay), &board_options);
+            }
+        }
+        // The chunks are spawned again uncovered, under the fading covers
+        for chunk in chunks {
+            if let Some(entity) = board.remove_chunk(chunk) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        if let Some(bomb) = bomb {
+            log::info!("Bomb !");
+            bomb_explosion_event_wr.send(BombExplosionEvent {
+                board: board.entity,
+                coordinates: bomb,
+            });
+        } else if board.is_completed() {
+            log::info!("Board completed");
+            board_completed_event_wr.send(BoardCompletedEvent { board: board.entity });
         }
     }
 }
 
+/// Removes the tile covers once their cascade delay is over
 pub fn uncover_tiles(
     mut commands: Commands,
-    mut board: ResMut<Board>,
-    children: Query<(Entity, &Parent), With<Uncover>>,
-    parents: Query<(&Coordinates, Option<&Bomb>, Option<&BombNeighbor>)>,
-    mut board_completed_event_wr: EventWriter<BoardCompletedEvent>,
-    mut bomb_explosion_event_wr: EventWriter<BombExplosionEvent>,
+    time: Res<Time>,
+    board_options: Res<BoardOptions>,
+    mut covers: Query<(Entity, &mut Uncover)>,
 ) {
-    for (entity, parent) in children.iter() {
-        commands
-            .entity(entity)
-            .despawn_recursive();
-        let (coords, bomb, bomb_counter) = match parents.get(parent.get()) {
-            Ok(v) => v,
-            Err(e) => {
-                log::error!("{}", e);
-                continue;
-            },
-        };
-        match board.try_uncover_tile(coords) {
-            None => log::debug!("Tiled to uncover an already uncoverd tile"),
-            Some(e) => log::debug!("Uncovered tile {} (entity: {:?})", coords, e),
+    for (entity, mut uncover) in covers.iter_mut() {
+        if uncover.delay.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Uncover>();
+            remove_cover(&mut commands, entity, &board_options.animations);
         }
-        if board.is_completed() {
-            log::info!("Board completed");
-            board_completed_event_wr.send(BoardCompletedEvent);
-        }
-        if bomb.is_some() {
-            log::info!("Bomb !");
-            bomb_explosion_event_wr.send(BombExplosionEvent);
-        }
-        else if bomb_counter.is_none() {
-            for entity in board.adjacent_covered_tiles(*coords) {
-                commands.entity(entity).insert(Uncover);
-            }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::components::Coordinates;
+    use crate::resources::board::CHUNK_SIZE;
+    use crate::resources::tile_map::TileMap;
+    use crate::spawn::tests::board_assets;
+
+    /// App playing a 500x500 board with every chunk spawned, as an empty entity,
+    /// and a bomb fence around the bottom left 20x20 corner
+    pub(crate) fn large_board_app() -> (App, Entity) {
+        let fence: Vec<Coordinates> = (0..=20)
+            .flat_map(|i| [Coordinates { x: 20, y: i }, Coordinates { x: i, y: 20 }])
+            .collect();
+        let mut board = Board::headless(TileMap::with_bombs(500, 500, &fence));
+        let mut app = App::new();
+        app.add_event::<TileTriggerEvent>()
+            .add_event::<BoardCompletedEvent>()
+            .add_event::<BombExplosionEvent>()
+            .add_event::<TilesUncoveredEvent>()
+            .insert_resource(BoardOptions {
+                animations: None,
+                ..Default::default()
+            })
+            .insert_resource(board_assets());
+        let side = 500 / CHUNK_SIZE + 1;
+        for chunk in (0..side).flat_map(|y| (0..side).map(move |x| Coordinates { x, y })) {
+            board.chunks.insert(chunk, app.world_mut().spawn_empty().id());
         }
+        let entity = app.world_mut().spawn(GameStatus::default()).id();
+        board.entity = entity;
+        app.world_mut().entity_mut(entity).insert(board);
+        (app, entity)
+    }
+
+    /// Chunks still spawned, and the chunk entities alive
+    pub(crate) fn spawned_chunks(app: &mut App, board: Entity) -> (usize, usize) {
+        let chunks: Vec<Entity> = app.world().get::<Board>(board).unwrap().chunks.values().copied().collect();
+        let alive = app.world_mut().query::<Entity>().iter(app.world()).count();
+        (chunks.len(), alive - 1)
+    }
+
+    #[test]
+    fn reveal_rebuilds_the_opening_chunks_only() {
+        let (mut app, board) = large_board_app();
+        app.add_systems(Update, trigger_event_handler);
+        assert_eq!(spawned_chunks(&mut app, board), (1024, 1024));
+        app.world_mut().send_event(TileTriggerEvent {
+            board,
+            coordinates: Coordinates { x: 3, y: 3 },
+        });
+        app.update();
+        // The opening and its border lie in the 4 bottom left chunks
+        assert_eq!(spawned_chunks(&mut app, board), (1020, 1020));
+        let board = app.world().get::<Board>(board).unwrap();
+        assert_eq!(board.covered_tiles.len(), 500 * 500 - 20 * 20);
+        assert!(!board.is_exploded());
+    }
+
+    #[test]
+    fn explosion_is_remembered() {
+        let (mut app, board) = large_board_app();
+        app.add_systems(Update, trigger_event_handler);
+        app.world_mut().send_event(TileTriggerEvent {
+            board,
+            coordinates: Coordinates { x: 20, y: 5 },
+        });
+        app.update();
+        let mut board = app.world_mut().get_mut::<Board>(board).unwrap();
+        assert!(board.is_exploded());
+        board.undo(crate::resources::GameMode::Practice);
+        assert!(!board.is_exploded());
     }
 }
@@ -0,0 +1,31 @@
+//! Game server, for the "Join local server" menu button.
+//!
+//! Usage: `server [coop|race] [address] [--replay file]`, the replay file
+//! being watched with `bevy_workspace --replay file`
+use bevy::log;
+use board_plugin::net::{GameServer, NetMode, ServerOptions, DEFAULT_PORT};
+
+fn usage() -> ! {
+    eprintln!("Usage: server [coop|race] [address] [--replay file]");
+    std::process::exit(2);
+}
+
+fn main() {
+    log::tracing_subscriber::fmt::init();
+    let mut options = ServerOptions::default();
+    let mut address = format!("127.0.0.1:{}", DEFAULT_PORT);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "coop" => options.mode = NetMode::Coop,
+            "race" => options.mode = NetMode::Race,
+            "--replay" => options.replay = Some(args.next().unwrap_or_else(|| usage()).into()),
+            _ if arg.contains(':') => address = arg,
+            _ => usage(),
+        }
+    }
+    if let Err(e) = GameServer::new(options).run(address) {
+        log::error!("Server stopped: {}", e);
+        std::process::exit(1);
+    }
+}
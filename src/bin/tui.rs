@@ -0,0 +1,323 @@
+//! Terminal frontend, playing the board rules without any window.
+//!
+//! Usage: `tui [width height mines] [--seed N]`
+//!
+//! Arrows or hjkl move the cursor, Space or Enter reveals a tile or chords a number,
+//! f marks, r restarts and q quits. Keys are read from any stdin, so a game can be
+//! scripted in CI: `printf ' q' | tui 9 9 10 --seed 1`
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use board_plugin::components::Coordinates;
+use board_plugin::resources::board::Board;
+use board_plugin::resources::tile::{Mark, Tile};
+use board_plugin::resources::tile_map::TileMap;
+use board_plugin::resources::GameStatus;
+
+/// Terminal key
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Char(char),
+}
+
+/// Game settings, from the command line
+#[derive(Debug, Copy, Clone)]
+struct Settings {
+    map_size: (u16, u16),
+    bomb_count: u16,
+    seed: Option<u64>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            map_size: (16, 16),
+            bomb_count: 40,
+            seed: None,
+        }
+    }
+}
+
+impl Settings {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut settings = Self::default();
+        let mut numbers = Vec::new();
+        while let Some(arg) = args.next() {
+            let seed = arg == "--seed";
+            let value = match seed {
+                true => args.next().ok_or("--seed needs a value")?,
+                false => arg,
+            };
+            let number = value.parse::<u64>().map_err(|_| format!("Unexpected {}", value))?;
+            match seed {
+                true => settings.seed = Some(number),
+                false => numbers.push(number),
+            }
+        }
+        match numbers.as_slice() {
+            [] => (),
+            [width, height, mines] => {
+                let side = |v: u64| u16::try_from(v).ok().filter(|v| (2..=200).contains(v));
+                let (Some(width), Some(height)) = (side(*width), side(*height)) else {
+                    return Err("Width and height must be between 2 and 200".to_string());
+                };
+                let max_bombs = (width as u64 * height as u64).saturating_sub(9);
+                if *mines == 0 || *mines > max_bombs {
+                    return Err(format!("Mines must be between 1 and {}", max_bombs));
+                }
+                settings.map_size = (width, height);
+                settings.bomb_count = *mines as u16;
+            }
+            _ => return Err("Expected width, height and mines".to_string()),
+        }
+        Ok(settings)
+    }
+}
+
+/// Game played in the terminal
+struct Game {
+    board: Board,
+    status: GameStatus,
+    cursor: Coordinates,
+    started: Option<Instant>,
+    /// Duration of the ended game
+    ended: Option<Duration>,
+}
+
+impl Game {
+    fn new(settings: &Settings) -> Self {
+        let (width, height) = settings.map_size;
+        let mut tile_map = TileMap::empty(width, height);
+        tile_map.set_bombs(settings.bomb_count, settings.seed.unwrap_or_else(rand::random));
+        let safe_start = tile_map.safe_start();
+        let mut board = Board::headless(tile_map);
+        if let Some(coordinates) = safe_start {
+            board.uncover_safe_start(coordinates);
+        }
+        Self {
+            board,
+            status: GameStatus::Playing,
+            cursor: safe_start.unwrap_or_default(),
+            started: None,
+            ended: None,
+        }
+    }
+
+    fn seconds(&self) -> u64 {
+        match (self.ended, self.started) {
+            (Some(duration), _) => duration.as_secs(),
+            (None, Some(started)) => started.elapsed().as_secs(),
+            (None, None) => 0,
+        }
+    }
+
+    fn move_cursor(&mut self, (dx, dy): (i32, i32)) {
+        let (width, height) = (self.board.tile_map.width() as i32, self.board.tile_map.height() as i32);
+        self.cursor.x = (self.cursor.x + dx).clamp(0, width - 1);
+        self.cursor.y = (self.cursor.y + dy).clamp(0, height - 1);
+    }
+
+    /// Reveals the tile under the cursor, or chords it when uncovered
+    fn trigger(&mut self) {
+        if self.status != GameStatus::Playing || self.board.marked_tiles.contains(&self.cursor) {
+            return;
+        }
+        let opening = match self.board.covered_tiles.contains(&self.cursor) {
+            true => self.board.reveal(self.cursor),
+            false => self.board.chord(self.cursor),
+        };
+        if opening.is_empty() {
+            return;
+        }
+        self.started.get_or_insert_with(Instant::now);
+        if self.board.is_exploded() {
+            self.end(GameStatus::Lost);
+        } else if self.board.is_completed() {
+            self.end(GameStatus::Won);
+        }
+    }
+
+    fn mark(&mut self) {
+        if self.status == GameStatus::Playing {
+            self.board.toggle_mark(&self.cursor, false);
+        }
+    }
+
+    fn end(&mut self, status: GameStatus) {
+        self.status = status;
+        self.ended = Some(self.started.map_or(Duration::ZERO, |s| s.elapsed()));
+    }
+
+    /// Draws the board, the top row first, with the status and help lines
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let flags = self.board.marked_tiles.len();
+        let mines_left = self.board.tile_map.bomb_count() as i64 - flags as i64;
+        let status = match self.status {
+            GameStatus::Playing => "",
+            GameStatus::Won => "  Board cleared!",
+            GameStatus::Lost => "  Boom! Game over",
+        };
+        write!(out, "\x1b[H\x1b[2J")?;
+        write!(out, "Mines: {}  Time: {}s{}\r\n", mines_left, self.seconds(), status)?;
+        for y in (0..self.board.tile_map.height() as i32).rev() {
+            for x in 0..self.board.tile_map.width() as i32 {
+                let coordinates = Coordinates { x, y };
+                let cell = self.cell(coordinates);
+                match coordinates == self.cursor {
+                    true => write!(out, "\x1b[7m{}\x1b[0m", cell)?,
+                    false => write!(out, "{}", cell)?,
+                }
+            }
+            write!(out, "\r\n")?;
+        }
+        write!(out, "arrows/hjkl move, space reveal/chord, f mark, r restart, q quit\r\n")?;
+        out.flush()
+    }
+
+    /// Colored text of a tile, two characters wide
+    fn cell(&self, coordinates: Coordinates) -> String {
+        let tile = self.board.tile_map.tile_at(coordinates).copied().unwrap_or(Tile::Empty);
+        let covered = self.board.covered_tiles.contains(&coordinates);
+        let mark = self.board.mark_at(&coordinates);
+        // Bombs and wrong flags are shown once the game is lost
+        let lost = self.status == GameStatus::Lost;
+        match (covered, mark, tile) {
+            (true, Some(Mark::Flag), tile) if lost && !tile.is_bomb() => "\x1b[31mX \x1b[0m".to_string(),
+            (true, Some(Mark::Flag), _) => "\x1b[91mF \x1b[0m".to_string(),
+            (true, _, Tile::Bomb) if lost => "\x1b[91m* \x1b[0m".to_string(),
+            (true, Some(Mark::Question), _) => "\x1b[93m? \x1b[0m".to_string(),
+            (true, None, _) => "\x1b[90m# \x1b[0m".to_string(),
+            (false, _, Tile::Bomb) => "\x1b[41m* \x1b[0m".to_string(),
+            (false, _, Tile::Empty) => "  ".to_string(),
+            (false, _, Tile::BombNeighbor(count)) => {
+                // Usual minesweeper number colors
+                let color = match count {
+                    1 => 94,
+                    2 => 32,
+                    3 => 91,
+                    4 => 34,
+                    5 => 31,
+                    6 => 36,
+                    7 => 35,
+                    _ => 37,
+                };
+                format!("\x1b[{}m{} \x1b[0m", color, count)
+            }
+        }
+    }
+}
+
+/// Raw terminal mode, restored on drop. Without a terminal, keys are read as they come
+struct RawMode {
+    saved: Option<String>,
+}
+
+impl RawMode {
+    fn enable() -> Self {
+        let saved = stty(&["-g"]).filter(|_| stty(&["raw", "-echo"]).is_some());
+        print!("\x1b[?25l");
+        Self { saved }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            stty(&[saved.trim()]);
+        }
+        print!("\x1b[?25h");
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Runs `stty` on the terminal, returning its output
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Reads the keys on a thread, so the timer is drawn while waiting
+fn read_keys() -> Receiver<Key> {
+    let (key_tx, key_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut bytes = io::stdin().lock().bytes().map_while(Result::ok);
+        while let Some(byte) = bytes.next() {
+            let key = match byte {
+                // Arrow escape sequences
+                0x1b => match (bytes.next(), bytes.next()) {
+                    (Some(b'['), Some(b'A')) => Key::Up,
+                    (Some(b'['), Some(b'B')) => Key::Down,
+                    (Some(b'['), Some(b'C')) => Key::Right,
+                    (Some(b'['), Some(b'D')) => Key::Left,
+                    _ => continue,
+                },
+                b'\r' | b'\n' => Key::Char(' '),
+                // Ctrl+C, as raw mode doesn't interrupt
+                0x03 => Key::Char('q'),
+                byte => Key::Char(byte as char),
+            };
+            if key_tx.send(key).is_err() {
+                return;
+            }
+        }
+        let _ = key_tx.send(Key::Char('q'));
+    });
+    key_rx
+}
+
+fn main() {
+    let settings = match Settings::from_args(std::env::args().skip(1)) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}\nUsage: tui [width height mines] [--seed N]", e);
+            std::process::exit(2);
+        }
+    };
+    let mut game = Game::new(&settings);
+    let keys = read_keys();
+    let raw_mode = RawMode::enable();
+    let mut out = io::stdout().lock();
+    loop {
+        if let Err(e) = game.draw(&mut out) {
+            drop(raw_mode);
+            eprintln!("Failed to draw: {}", e);
+            std::process::exit(1);
+        }
+        let key = match keys.recv_timeout(Duration::from_millis(250)) {
+            Ok(key) => key,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        match key {
+            Key::Up | Key::Char('k') => game.move_cursor((0, 1)),
+            Key::Down | Key::Char('j') => game.move_cursor((0, -1)),
+            Key::Left | Key::Char('h') => game.move_cursor((-1, 0)),
+            Key::Right | Key::Char('l') => game.move_cursor((1, 0)),
+            Key::Char(' ') => game.trigger(),
+            Key::Char('f') => game.mark(),
+            Key::Char('r') => game = Game::new(&Settings { seed: None, ..settings }),
+            Key::Char('q') => break,
+            Key::Char(_) => (),
+        }
+    }
+    drop(out);
+    drop(raw_mode);
+    // Result line, for scripted games
+    match game.status {
+        GameStatus::Playing => println!("Quit after {} seconds", game.seconds()),
+        GameStatus::Won => println!("Won in {} seconds", game.seconds()),
+        GameStatus::Lost => println!("Lost after {} seconds", game.seconds()),
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::Instant;
+
+use board_plugin::resources::board::Board;
+use board_plugin::resources::BoardOptions;
+use board_plugin::solver;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cli::Options;
+
+/// Plays games with the solver, without window, and prints their statistics. The games
+/// of a seed are always the same
+pub fn print_statistics(options: &Options, games: u32) {
+    let board_options = BoardOptions {
+        map_size: options.map_size,
+        bomb_count: options.bomb_count,
+        safe_start: true,
+        ..Default::default()
+    };
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let started = Instant::now();
+    let (mut won, mut guesses, mut tiles_left) = (0, 0, 0);
+    for _ in 0..games {
+        let mut board = Board::random(&board_options, rng.gen());
+        let game = solver::play_game(&mut board, &mut rng);
+        guesses += game.guesses as u64;
+        match game.won {
+            true => won += 1,
+            false => tiles_left += game.tiles_left as u64,
+        }
+    }
+    let lost = games - won;
+    let (width, height) = options.map_size;
+    println!("{} games on {}x{} with {} mines, seed {}", games, width, height, options.bomb_count, seed);
+    println!("won: {} ({:.1}%)", won, won as f64 * 100. / games.max(1) as f64);
+    println!("guesses per game: {:.2}", guesses as f64 / games.max(1) as f64);
+    println!("safe tiles left per loss: {:.1}", tiles_left as f64 / lost.max(1) as f64);
+    println!("played in {:.2}s", started.elapsed().as_secs_f64());
+}
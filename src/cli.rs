@@ -0,0 +1,218 @@
+use std::path::PathBuf;
+
+use bevy::prelude::Resource;
+use board_plugin::resources::BoardOptions;
+
+use crate::menu::{CustomBoard, Difficulty};
+use crate::settings::Settings;
+
+pub const USAGE: &str = "Usage: bevy_workspace [options]
+  --difficulty <beginner|intermediate|expert>
+  --width <W> --height <H> --mines <M>
+  --seed <N>           board seed
+  --theme <file>       theme asset, e.g. themes/dark.theme.ron
+  --window <WxH>       window size in pixels
+  --layout <file>      layout or saved game, as text or .mbf
+  --replay <file>      replay of the server, or .rmv replay
+  --spectate [player]  watches a player of the local server
+  --daily              plays the daily challenge
+  --puzzle <file>      plays a puzzle layout, untimed and lost on a wrong reveal
+  --generate-puzzle <file>  writes a no-guess puzzle of the board options
+  --verify-daily <result>  checks a shared daily challenge result
+  --headless --bot <N> plays N games without window and prints statistics";
+
+/// Command line options, over the user settings. A board option starts the game
+/// without the main menu
+#[derive(Debug, Clone, Resource)]
+pub struct Options {
+    pub map_size: (u16, u16),
+    pub bomb_count: u16,
+    pub seed: Option<u64>,
+    pub layout: Option<PathBuf>,
+    /// Theme asset path
+    pub theme: String,
+    pub window: (f32, f32),
+    pub replay: Option<PathBuf>,
+    /// Watched player of the local server
+    pub spectate: Option<usize>,
+    pub daily: bool,
+    /// Puzzle layout to play
+    pub puzzle: Option<PathBuf>,
+    /// File to write a generated puzzle to
+    pub generate_puzzle: Option<PathBuf>,
+    /// Shared daily challenge result to check
+    pub verify_daily: Option<String>,
+    /// Games played by the bot, without window
+    pub bot: Option<u32>,
+    /// Was any board option given
+    pub start_game: bool,
+}
+
+impl Options {
+    /// Parses the options, defaulting to the user settings
+    pub fn from_args(args: impl Iterator<Item = String>, settings: &Settings) -> Result<Self, String> {
+        let mut options = Self {
+            map_size: settings.board.map_size,
+            bomb_count: settings.board.bomb_count,
+            seed: None,
+            layout: None,
+            theme: settings.theme.clone(),
+            window: settings.window.size,
+            replay: None,
+            spectate: None,
+            daily: false,
+            puzzle: None,
+            generate_puzzle: None,
+            verify_daily: None,
+            bot: None,
+            start_game: false,
+        };
+        let mut args = args.peekable();
+        let mut headless = false;
+        let (mut width, mut height, mut mines) = (None, None, None);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--difficulty" => {
+                    let value = value()?;
+                    let difficulty = Difficulty::ALL
+                        .into_iter()
+                        .find(|d| format!("{:?}", d).eq_ignore_ascii_case(&value))
+                        .ok_or(format!("Unknown difficulty {}", value))?;
+                    (options.map_size, options.bomb_count) = difficulty.board();
+                    options.start_game = true;
+                }
+                "--width" => width = Some(number(&arg, &value()?)?),
+                "--height" => height = Some(number(&arg, &value()?)?),
+                "--mines" => mines = Some(number(&arg, &value()?)?),
+                "--seed" => {
+                    options.seed = Some(number(&arg, &value()?)?);
+                    options.start_game = true;
+                }
+                "--theme" => options.theme = value()?,
+                "--window" => {
+                    let value = value()?;
+                    let size = value
+                        .split_once('x')
+                        .and_then(|(w, h)| Some((w.parse::<f32>().ok()?, h.parse::<f32>().ok()?)))
+                        .filter(|(w, h)| *w >= 100. && *h >= 100.);
+                    options.window = size.ok_or(format!("Invalid window size {}", value))?;
+                }
+                "--layout" => {
+                    options.layout = Some(PathBuf::from(value()?));
+                    options.start_game = true;
+                }
+                "--replay" => options.replay = Some(PathBuf::from(value()?)),
+                "--spectate" => {
+                    // The player is optional
+                    let player = args.next_if(|p| p.parse::<usize>().is_ok());
+                    options.spectate = Some(player.map_or(0, |p| p.parse().unwrap_or_default()));
+                }
+                "--daily" => {
+                    options.daily = true;
+                    options.start_game = true;
+                }
+                "--puzzle" => {
+                    options.puzzle = Some(PathBuf::from(value()?));
+                    options.start_game = true;
+                }
+                "--generate-puzzle" => options.generate_puzzle = Some(PathBuf::from(value()?)),
+                "--verify-daily" => options.verify_daily = Some(value()?),
+                "--headless" => headless = true,
+                "--bot" => options.bot = Some(number(&arg, &value()?)?),
+                _ => return Err(format!("Unexpected {}", arg)),
+            }
+        }
+        if width.is_some() || height.is_some() || mines.is_some() {
+            let (w, h) = options.map_size;
+            let small = |v: u64| u16::try_from(v).unwrap_or(u16::MAX);
+            let map_size = (small(width.unwrap_or(w as u64)), small(height.unwrap_or(h as u64)));
+            let mines = small(mines.unwrap_or(options.bomb_count as u64));
+            check_board(map_size, mines, settings.board.safe_start)?;
+            options.map_size = map_size;
+            options.bomb_count = mines;
+            options.start_game = true;
+        }
+        if headless != options.bot.is_some() {
+            return Err("--headless and --bot <N> go together".to_string());
+        }
+        Ok(options)
+    }
+
+    /// Overrides the board options of the user settings for this run, without saving them
+    pub fn apply(&self, board_options: &mut BoardOptions) {
+        board_options.map_size = self.map_size;
+        board_options.bomb_count = self.bomb_count;
+        board_options.seed = self.seed;
+        board_options.layout = self.layout.clone();
+    }
+}
+
+/// Checks a board can be generated, as the custom board dialog does
+pub fn check_board(map_size: (u16, u16), bomb_count: u16, safe_start: bool) -> Result<(), String> {
+    let (width, height) = map_size;
+    CustomBoard {
+        width,
+        height,
+        bomb_count,
+    }
+    .validate(safe_start)
+}
+
+fn number<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid {} value {}", arg, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::from_args(args.iter().map(|a| a.to_string()), &Settings::default())
+    }
+
+    #[test]
+    fn board_options() {
+        let options = parse(&["--width", "30", "--height", "16", "--mines", "99", "--seed", "7"]).unwrap();
+        assert_eq!((options.map_size, options.bomb_count, options.seed), ((30, 16), 99, Some(7)));
+        assert!(options.start_game);
+
+        let options = parse(&["--difficulty", "Expert", "--theme", "themes/dark.theme.ron"]).unwrap();
+        assert_eq!((options.map_size, options.bomb_count), Difficulty::Expert.board());
+        assert_eq!(options.theme, "themes/dark.theme.ron");
+
+        // Huge boards are drawn in chunks
+        let options = parse(&["--width", "500", "--height", "500", "--mines", "50000"]).unwrap();
+        assert_eq!(options.map_size, (500, 500));
+
+        let options = parse(&["--window", "640x480", "--spectate"]).unwrap();
+        assert_eq!((options.window, options.spectate), ((640., 480.), Some(0)));
+        assert!(!options.start_game);
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert_eq!(parse(&["--bogus"]).err(), Some("Unexpected --bogus".to_string()));
+        assert_eq!(parse(&["--width"]).err(), Some("--width needs a value".to_string()));
+        assert_eq!(parse(&["--mines", "many"]).err(), Some("Invalid --mines value many".to_string()));
+        assert!(parse(&["--difficulty", "insane"]).is_err());
+        assert!(parse(&["--window", "10x10"]).is_err());
+        assert!(parse(&["--headless"]).is_err());
+    }
+
+    #[test]
+    fn out_of_range_boards() {
+        let error = |args: &[&str]| parse(args).err().unwrap_or_default();
+        assert_eq!(error(&["--width", "501"]), "Width and height must be between 2 and 500");
+        assert_eq!(error(&["--height", "1"]), "Width and height must be between 2 and 500");
+        assert_eq!(error(&["--mines", "0"]), "At least 1 mine is needed");
+        assert_eq!(
+            error(&["--width", "9", "--height", "9", "--mines", "73"]),
+            "At most 72 mines fit on this board"
+        );
+        assert_eq!(
+            error(&["--width", "3", "--height", "3", "--mines", "1"]),
+            "A 3x3 board is too small for a mine besides the safe start"
+        );
+    }
+}
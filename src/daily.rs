@@ -0,0 +1,498 @@
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::events::{TileMarkEvent, TileTriggerEvent};
+use board_plugin::resources::board::Board;
+use board_plugin::resources::{BoardOptions, GameMode, GameStatus, GameTimer};
+use serde::{Deserialize, Serialize};
+
+use crate::menu::{Difficulty, MenuRoot};
+use crate::settings::{leave_challenge, Challenge, Settings};
+use crate::{AppState, GamePhase};
+
+/// Calendar day, in UTC
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self::from_days((seconds / 86_400) as i64)
+    }
+
+    /// Date of a day counted from 1970-01-01, from Howard Hinnant's `civil_from_days`
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + (month <= 2) as i64) as i32;
+        Self { year, month, day }
+    }
+
+    /// Days since 1970-01-01, from Howard Hinnant's `days_from_civil`
+    pub fn days(&self) -> i64 {
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let month = self.month as i64;
+        let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// Difficulty of the day, cycling through the presets
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::ALL[self.days().rem_euclid(Difficulty::ALL.len() as i64) as usize]
+    }
+
+    /// Board seed of the day, the same for everyone
+    pub fn seed(&self) -> u64 {
+        // SplitMix64 finalizer, so consecutive days get unrelated boards
+        let mut z = (self.days() as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Board options of the day, over the user options
+    pub fn board_options(&self, options: &BoardOptions) -> BoardOptions {
+        let (map_size, bomb_count) = self.difficulty().board();
+        BoardOptions {
+            map_size,
+            bomb_count,
+            safe_start: true,
+            seed: Some(self.seed()),
+            layout: None,
+            mode: GameMode::Ranked,
+            ..options.clone()
+        }
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid date {}", s);
+        let mut parts = s.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+        let (Some(Some(year)), Some(Some(month)), Some(Some(day))) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        let date = Self {
+            year: i32::try_from(year).map_err(|_| invalid())?,
+            month: u32::try_from(month).map_err(|_| invalid())?,
+            day: u32::try_from(day).map_err(|_| invalid())?,
+        };
+        // Rejects the days a month doesn't have
+        match Self::from_days(date.days()) == date {
+            true => Ok(date),
+            false => Err(invalid()),
+        }
+    }
+}
+
+/// Result of a daily challenge, shared as a line of text:
+/// `Minesweeper daily 2026-10-19 won 73.4s 3BV 121 1.65/s 190 clicks #1f2e3d4c`.
+/// The checksum catches edits of the line, and the 3BV is verified against the board
+/// of the day
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DailyResult {
+    pub date: Date,
+    pub won: bool,
+    /// Game time, in tenths of a second
+    pub tenths: u64,
+    pub three_bv: u32,
+    pub clicks: u32,
+}
+
+impl DailyResult {
+    /// 3BV per second
+    pub fn three_bv_per_second(&self) -> f64 {
+        self.three_bv as f64 * 10. / self.tenths.max(1) as f64
+    }
+
+    /// FNV-1a hash of the result and the seed of the day
+    fn checksum(&self) -> u32 {
+        let fields = format!(
+            "{}|{}|{}|{}|{}|{}",
+            self.date, self.won, self.tenths, self.three_bv, self.clicks, self.date.seed()
+        );
+        fields.bytes().fold(0x811c_9dc5, |hash: u32, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193))
+    }
+
+    /// Checks a shared result against the board of its day
+    pub fn verify(share: &str) -> Result<Self, String> {
+        let result: Self = share.parse()?;
+        let options = result.date.board_options(&BoardOptions::default());
+        let board = Board::random(&options, result.date.seed());
+        let three_bv = board.tile_map.three_bv();
+        if result.three_bv != three_bv {
+            return Err(format!("The board of {} has a 3BV of {}, not {}", result.date, three_bv, result.three_bv));
+        }
+        Ok(result)
+    }
+}
+
+impl Display for DailyResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Minesweeper daily {} {} {}.{}s 3BV {} {:.2}/s {} clicks #{:08x}",
+            self.date,
+            if self.won { "won" } else { "lost" },
+            self.tenths / 10,
+            self.tenths % 10,
+            self.three_bv,
+            self.three_bv_per_second(),
+            self.clicks,
+            self.checksum(),
+        )
+    }
+}
+
+impl FromStr for DailyResult {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let invalid = || "Not a daily challenge result".to_string();
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let ["Minesweeper", "daily", date, status, time, "3BV", three_bv, _, clicks, "clicks", _] = words[..] else {
+            return Err(invalid());
+        };
+        let (seconds, tenth) = time.strip_suffix('s').and_then(|t| t.split_once('.')).ok_or_else(invalid)?;
+        let tenth: u64 = tenth.parse().ok().filter(|t| *t < 10).ok_or_else(invalid)?;
+        let result = Self {
+            date: date.parse()?,
+            won: match status {
+                "won" => true,
+                "lost" => false,
+                _ => return Err(invalid()),
+            },
+            tenths: seconds.parse::<u64>().map_err(|_| invalid())? * 10 + tenth,
+            three_bv: three_bv.parse().map_err(|_| invalid())?,
+            clicks: clicks.parse().map_err(|_| invalid())?,
+        };
+        // Also checks the 3BV/s and checksum, formatted from the other fields
+        match result.to_string() == words.join(" ") {
+            true => Ok(result),
+            false => Err("The result was edited".to_string()),
+        }
+    }
+}
+
+/// Last ranked attempt, in `daily.ron` next to the settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DailyRecord {
+    date: String,
+    /// Shared result, once the game is over
+    result: Option<String>,
+}
+
+impl DailyRecord {
+    fn path() -> Option<PathBuf> {
+        Some(Settings::path()?.with_file_name("daily.ron"))
+    }
+
+    fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| ron::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let saved = Self::path().ok_or("No config directory".to_string()).and_then(|path| {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            let content = ron::to_string(self).map_err(|e| e.to_string())?;
+            std::fs::write(path, content).map_err(|e| e.to_string())
+        });
+        if let Err(e) = saved {
+            log::error!("Failed to record the daily challenge: {}", e);
+        }
+    }
+}
+
+/// Daily challenge being played. Only the first game of the day is ranked, the next
+/// ones are practice
+#[derive(Debug, Clone, Resource)]
+pub struct DailyChallenge {
+    pub date: Date,
+    pub ranked: bool,
+    /// Triggers and marks of the game
+    pub clicks: u32,
+    /// Board options to restore when leaving the challenge
+    options: Option<BoardOptions>,
+}
+
+impl DailyChallenge {
+    /// Challenge of the day, ranked unless already played
+    pub fn today() -> Self {
+        let date = Date::today();
+        let ranked = DailyRecord::load().date != date.to_string();
+        if !ranked {
+            log::info!("daily challenge already played today, this game is practice");
+        }
+        Self {
+            date,
+            ranked,
+            clicks: 0,
+            options: None,
+        }
+    }
+
+    /// Replaces the board options with the ones of the day
+    pub fn apply(&mut self, board_options: &mut BoardOptions) {
+        self.options = Some(board_options.clone());
+        *board_options = self.date.board_options(board_options);
+        if !self.ranked {
+            board_options.mode = GameMode::Practice;
+        }
+    }
+}
+
+impl Challenge for DailyChallenge {
+    fn user_options(&self) -> Option<&BoardOptions> {
+        self.options.as_ref()
+    }
+}
+
+/// Daily challenge: the board of the UTC day, ranked once
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::MainMenu), leave_challenge::<DailyChallenge>);
+        app.add_systems(OnEnter(AppState::InGame), start_attempt.run_if(resource_exists::<DailyChallenge>));
+        app.add_systems(Update, count_clicks
+            .run_if(in_state(AppState::InGame))
+            .run_if(resource_exists::<DailyChallenge>));
+        app.add_systems(OnEnter(GamePhase::Over), finish_attempt.run_if(resource_exists::<DailyChallenge>));
+    }
+}
+
+/// Uses the ranked attempt of the day as soon as the board is shown
+fn start_attempt(mut challenge: ResMut<DailyChallenge>) {
+    challenge.clicks = 0;
+    if challenge.ranked {
+        DailyRecord {
+            date: challenge.date.to_string(),
+            result: None,
+        }
+        .save();
+    }
+}
+
+fn count_clicks(
+    mut challenge: ResMut<DailyChallenge>,
+    mut triggers: EventReader<TileTriggerEvent>,
+    mut marks: EventReader<TileMarkEvent>,
+) {
+    let clicks = triggers.read().count() + marks.read().count();
+    if clicks > 0 {
+        challenge.clicks += clicks as u32;
+    }
+}
+
+/// Shows the result of the game, recorded if ranked. The next games are practice
+fn finish_attempt(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut challenge: ResMut<DailyChallenge>,
+    mut board_options: ResMut<BoardOptions>,
+    boards: Query<(&Board, &GameStatus, &GameTimer)>,
+) {
+    let Some((board, status, timer)) = boards.iter().next() else {
+        return;
+    };
+    let result = DailyResult {
+        date: challenge.date,
+        won: *status == GameStatus::Won,
+        tenths: timer.elapsed.as_millis() as u64 / 100,
+        three_bv: board.tile_map.three_bv(),
+        clicks: challenge.clicks,
+    };
+    let share = result.to_string();
+    let label = match challenge.ranked {
+        true => {
+            DailyRecord {
+                date: challenge.date.to_string(),
+                result: Some(share.clone()),
+            }
+            .save();
+            log::info!("daily challenge result: {}", share);
+            share
+        }
+        false => format!("Practice: {}", share),
+    };
+    challenge.ranked = false;
+    board_options.mode = GameMode::Practice;
+    commands
+        .spawn(TextBundle::from_section(
+            label,
+            TextStyle {
+                font: asset_server.load("fonts/pixeled.ttf"),
+                font_size: 10.,
+                color: Color::WHITE,
+            },
+        ).with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.),
+            left: Val::Px(8.),
+            ..Default::default()
+        }))
+        .insert(Name::new("Daily Result"))
+        .insert(MenuRoot);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    #[test]
+    fn days() {
+        assert_eq!(Date::from_days(0), date(1970, 1, 1));
+        assert_eq!(Date::from_days(-1), date(1969, 12, 31));
+        assert_eq!(Date::from_days(11_016), date(2000, 2, 29));
+        assert_eq!(Date::from_days(20_745), date(2026, 10, 19));
+        for days in (-800_000..800_000).step_by(97) {
+            assert_eq!(Date::from_days(days).days(), days);
+        }
+    }
+
+    #[test]
+    fn parse_dates() {
+        assert_eq!("2026-10-19".parse(), Ok(date(2026, 10, 19)));
+        assert_eq!(date(812, 3, 4).to_string(), "0812-03-04");
+        // Leap years
+        assert_eq!("2024-02-29".parse(), Ok(date(2024, 2, 29)));
+        assert_eq!("2000-02-29".parse(), Ok(date(2000, 2, 29)));
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("1900-02-29".parse::<Date>().is_err());
+        for invalid in ["2026-04-31", "2026-13-01", "2026-00-10", "2026-10-00", "2026-10", "today", ""] {
+            assert!(invalid.parse::<Date>().is_err(), "{}", invalid);
+        }
+    }
+
+    fn result() -> DailyResult {
+        DailyResult {
+            date: date(2026, 10, 19),
+            won: true,
+            tenths: 734,
+            three_bv: 121,
+            clicks: 190,
+        }
+    }
+
+    #[test]
+    fn result_round_trip() {
+        let share = result().to_string();
+        assert!(share.starts_with("Minesweeper daily 2026-10-19 won 73.4s 3BV 121 1.65/s 190 clicks #"));
+        assert_eq!(share.parse(), Ok(result()));
+        let lost = DailyResult { won: false, tenths: 5, ..result() };
+        assert_eq!(lost.to_string().parse(), Ok(lost));
+    }
+
+    #[test]
+    fn edited_results() {
+        let share = result().to_string();
+        for edited in [
+            share.replace("73.4s", "63.4s"),
+            share.replace("190 clicks", "90 clicks"),
+            share.replace("2026-10-19", "2026-10-18"),
+            share.replace(" won ", " lost "),
+            format!("{}0", share),
+        ] {
+            assert_eq!(edited.parse::<DailyResult>(), Err("The result was edited".to_string()));
+        }
+        assert!("Minesweeper daily".parse::<DailyResult>().is_err());
+        assert!(share.replace("73.4s", "73.45s").parse::<DailyResult>().is_err());
+    }
+
+    /// App entering the main menu on startup, with the board options inserted by a
+    /// startup system as `setup_board` does
+    fn menu_app(challenge: Option<DailyChallenge>) -> App {
+        let mut app = App::new();
+        app.add_plugins(bevy::state::app::StatesPlugin)
+            .init_state::<AppState>()
+            .add_event::<TileTriggerEvent>()
+            .add_event::<TileMarkEvent>()
+            .add_plugins(DailyPlugin)
+            .add_systems(Startup, |mut commands: Commands| {
+                commands.insert_resource(BoardOptions::default());
+            });
+        if let Some(challenge) = challenge {
+            app.insert_resource(challenge);
+        }
+        app
+    }
+
+    #[test]
+    fn startup_in_main_menu() {
+        let mut app = menu_app(None);
+        app.update();
+        assert!(app.world().contains_resource::<BoardOptions>());
+    }
+
+    #[test]
+    fn leaving_restores_the_user_board() {
+        // Practice, so the attempt isn't recorded
+        let mut challenge = DailyChallenge {
+            ranked: false,
+            ..DailyChallenge::today()
+        };
+        let mut user_options = BoardOptions {
+            map_size: (12, 34),
+            ..Default::default()
+        };
+        challenge.apply(&mut user_options);
+        let mut app = menu_app(Some(challenge));
+        app.update();
+        app.world_mut().insert_resource(user_options);
+        app.world_mut().resource_mut::<NextState<AppState>>().set(AppState::InGame);
+        app.update();
+        app.world_mut().resource_mut::<NextState<AppState>>().set(AppState::MainMenu);
+        app.update();
+        assert_eq!(app.world().resource::<BoardOptions>().map_size, (12, 34));
+        assert!(!app.world().contains_resource::<DailyChallenge>());
+    }
+
+    #[test]
+    fn verify() {
+        let date = date(2026, 10, 19);
+        let options = date.board_options(&BoardOptions::default());
+        let three_bv = Board::random(&options, date.seed()).tile_map.three_bv();
+        let result = DailyResult { date, three_bv, ..result() };
+        assert_eq!(DailyResult::verify(&result.to_string()), Ok(result));
+        // A consistent line, but not the board of the day
+        let other = DailyResult { three_bv: three_bv + 1, ..result };
+        assert!(DailyResult::verify(&other.to_string()).is_err());
+        assert!(DailyResult::verify(&result.to_string().replace("190", "19")).is_err());
+    }
+}
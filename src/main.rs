@@ -1,5 +1,8 @@
 use bevy::prelude::*;
-use board_plugin::resources::{BoardAssets, BoardOptions, SpriteMaterial};
+use board_plugin::resources::{
+    BoardAssets, BoardLayout, BoardPlayers, BoardSounds, BoardTheme, NumberStyle, Palette,
+    SoundOptions, SpriteMaterial,
+};
 use bevy::log;
 
 #[cfg(feature = "debug")]
@@ -7,16 +10,81 @@ use bevy::log;
 // use bevy_inspector_egui::quick::WorldInspectorPlugin;
 // bevy_inspector_egui new version
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use board_plugin::BoardPlugin;
+use board_plugin::{BoardPlugin, EndlessBoardPlugin};
+use board_plugin::components::RemoteBoard;
+use board_plugin::formats;
+use board_plugin::net::NetClient;
+use board_plugin::resources::board::Board;
+
+mod bot;
+mod cli;
+mod daily;
+mod menu;
+mod online;
+mod puzzle;
+mod settings;
+
+use settings::{Settings, SettingsPlugin};
 
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash, States)]
 pub enum AppState {
     #[default]
+    MainMenu,
+    CustomBoard,
+    /// Waits for the round of the game server
+    Connecting,
+    /// Waits for the theme before (re)starting a game
+    Loading,
     InGame,
-    Out,
+    Endless,
+}
+
+/// Phase of a board game, following the `GameStatus` of the boards
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, SubStates)]
+#[source(AppState = AppState::InGame)]
+pub enum GamePhase {
+    #[default]
+    Playing,
+    Over,
 }
 
+/// Index of the selected theme in `THEMES`
+#[derive(Debug, Copy, Clone, Default, Resource)]
+pub struct ThemeChoice(pub usize);
+
 fn main() {
+    let (settings, warnings) = Settings::load();
+    let options = match cli::Options::from_args(std::env::args().skip(1), &settings) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Some(share) = &options.verify_daily {
+        match daily::DailyResult::verify(share) {
+            Ok(result) => println!("Valid result of the {} daily challenge", result.date),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(path) = &options.generate_puzzle {
+        match puzzle::generate(&options, path) {
+            Ok(()) => println!("Puzzle written to {}", path.display()),
+            Err(e) => {
+                eprintln!("Failed to write {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(games) = options.bot {
+        bot::print_statistics(&options, games);
+        return;
+    }
     let mut app = App::new();
 
     // window setup
@@ -30,7 +98,10 @@ fn main() {
     // bevy version 0.10
     let window = Window {
         title: "Mine Sweeper!".to_string(),
-        resolution: (700., 800.).into(),
+        resolution: options.window.into(),
+        position: settings.window.position.map_or(WindowPosition::Automatic, |(x, y)| {
+            WindowPosition::At(IVec2::new(x, y))
+        }),
         ..Default::default()
     };
     let primary_window = Some(window);
@@ -38,6 +109,9 @@ fn main() {
         primary_window,
         ..default()
     }));
+    for warning in warnings {
+        log::warn!("{}", warning);
+    }
 
     #[cfg(feature = "debug")]
     // debug hierarchy inspector
@@ -47,18 +121,74 @@ fn main() {
     // app.add_startup_system(camera_setup)
     // bevy version 0.11
     app.add_systems(Startup,setup_board);
-    app.init_state::<AppState>();
+    // A spectator from the command line starts watching right away, and a board
+    // option starts the game
+    match online::spectator(&options) {
+        Some(Ok(client)) => {
+            app.insert_resource(client);
+            app.insert_state(AppState::Connecting);
+        }
+        Some(Err(e)) => {
+            log::error!("Failed to start spectating: {}", e);
+            app.init_state::<AppState>();
+        }
+        None if options.start_game => {
+            app.insert_state(AppState::Loading);
+        }
+        None => {
+            app.init_state::<AppState>();
+        }
+    }
+    app.insert_resource(ThemeChoice(THEMES.iter().position(|t| *t == options.theme).unwrap_or_default()));
+    app.insert_resource(settings.players.clone());
+    app.insert_resource(settings.sound.clone());
+    if options.daily {
+        app.insert_resource(daily::DailyChallenge::today());
+        app.insert_resource(BoardPlayers::default());
+    }
+    if let Some(path) = &options.puzzle {
+        match puzzle::PuzzleChallenge::load(path) {
+            Ok(puzzle) => {
+                app.insert_resource(puzzle);
+            }
+            Err(e) => {
+                eprintln!("Failed to load {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+    app.insert_resource(settings);
+    app.insert_resource(options);
+    app.add_sub_state::<GamePhase>();
     app.add_plugins(BoardPlugin {
-        running_state:AppState::Out,
+        running_state:AppState::InGame,
+    });
+    app.add_plugins(EndlessBoardPlugin {
+        running_state:AppState::Endless,
     });
 
     // app.add_plugins(DefaultPlugins);
     app.add_systems(Startup, camera_setup);
-    app.add_systems(Update, state_handler);
+    app.add_plugins(menu::MenuPlugin);
+    app.add_plugins(online::OnlinePlugin);
+    app.add_plugins(SettingsPlugin);
+    app.add_plugins(daily::DailyPlugin);
+    app.add_plugins(puzzle::PuzzlePlugin);
+    app.add_systems(Update, (state_handler, theme_handler, sound_handler, number_style_handler));
+    app.add_systems(Update, finish_loading.run_if(in_state(AppState::Loading)));
+    app.add_systems(Update, (follow_game_status, export_handler).run_if(in_state(AppState::InGame)));
     // Run the app
     app.run();
 }
 
+/// Theme files, cycled with the T key or the main menu theme button
+pub const THEMES: [&str; 4] = [
+    "themes/classic.theme.ron",
+    "themes/dark.theme.ron",
+    "themes/atlas.theme.ron",
+    "themes/high_contrast.theme.ron",
+];
+
 fn camera_setup(mut commands: Commands) {
     // bevy version 0.8
     // commands.spawn_bandle(OrthographicCameraBandle::new_2d());
@@ -66,37 +196,129 @@ fn camera_setup(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
 
-fn state_handler(cur_state:ResMut<State<AppState>>,mut next_state:ResMut<NextState<AppState>>, keys: Res<ButtonInput<KeyCode>>) {
-    if keys.just_pressed(KeyCode::KeyC) {
-        log::debug!("cleaning detected");
-        if cur_state.get() == &AppState::InGame {
-            log::info!("clearing game");
-            next_state.set(AppState::Out);
+/// Escape leaves the game for the main menu
+fn state_handler(cur_state: Res<State<AppState>>, mut next_state: ResMut<NextState<AppState>>, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        match cur_state.get() {
+            AppState::InGame | AppState::Endless | AppState::CustomBoard | AppState::Connecting => {
+                log::info!("back to main menu");
+                next_state.set(AppState::MainMenu);
+            }
+            _ => (),
         }
     }
-    if keys.just_pressed(KeyCode::KeyG) {
-        log::debug!("loading detected");
-        if cur_state.get() == &AppState::Out {
-            log::info!("loading game");
-            next_state.set(AppState::InGame);
-       }
+}
+
+/// Starts the game once the theme is loaded, or failed to
+fn finish_loading(
+    board_theme: Option<Res<BoardTheme>>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let ready = board_theme.is_none_or(|t| {
+        asset_server.is_loaded_with_dependencies(&t.0)
+            || matches!(asset_server.get_load_state(&t.0), Some(bevy::asset::LoadState::Failed(_)))
+    });
+    if ready {
+        next_state.set(AppState::InGame);
+    }
+}
+
+/// Shows the game over screen when the game or race is over, and hides it when undone
+fn follow_game_status(
+    boards: menu::BoardResults,
+    client: Option<Res<NetClient>>,
+    phase: Res<State<GamePhase>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+) {
+    let target = match menu::game_outcome(&boards, client.as_deref()) {
+        None => GamePhase::Playing,
+        Some(_) => GamePhase::Over,
+    };
+    if *phase.get() != target {
+        next_phase.set(target);
+    }
+}
+
+fn theme_handler(
+    mut commands: Commands,
+    mut current: ResMut<ThemeChoice>,
+    keys: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+) {
+    if keys.just_pressed(KeyCode::KeyT) {
+        current.0 = (current.0 + 1) % THEMES.len();
+        log::info!("switching theme to {}", THEMES[current.0]);
+        commands.insert_resource(BoardTheme(asset_server.load(THEMES[current.0])));
+    }
+}
+
+/// M toggles the sound effects
+fn sound_handler(mut sound_options: ResMut<SoundOptions>, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::KeyM) {
+        sound_options.muted = !sound_options.muted;
+        log::info!("sound muted: {}", sound_options.muted);
+    }
+}
+
+/// P cycles the number palettes, from the theme colors, O toggles the number glyphs
+fn number_style_handler(mut number_style: ResMut<NumberStyle>, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::KeyP) {
+        number_style.palette = match number_style.palette {
+            None => Some(Palette::ALL[0]),
+            Some(palette) => Palette::ALL
+                .iter()
+                .position(|p| *p == palette)
+                .and_then(|i| Palette::ALL.get(i + 1))
+                .copied(),
+        };
+        log::info!("number palette: {:?}", number_style.palette);
+    }
+    if keys.just_pressed(KeyCode::KeyO) {
+        number_style.glyphs = !number_style.glyphs;
+        log::info!("number glyphs: {}", number_style.glyphs);
+    }
+}
+
+/// Ctrl+S saves the game of a local board to `saved_game.txt`, to be resumed with
+/// `--layout`, and Ctrl+Shift+S exports its bombs to `board.mbf`
+fn export_handler(boards: Query<&Board, Without<RemoteBoard>>, keys: Res<ButtonInput<KeyCode>>) {
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !ctrl || !keys.just_pressed(KeyCode::KeyS) {
+        return;
+    }
+    let Some(board) = boards.iter().next() else {
+        return;
+    };
+    let result = match keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        true => formats::save_mbf(&board.tile_map, "board.mbf").map(|_| "board.mbf"),
+        false => std::fs::write("saved_game.txt", BoardLayout::from_board(board).to_string())
+            .map(|_| "saved_game.txt")
+            .map_err(formats::FormatError::from),
+    };
+    match result {
+        Ok(path) => log::info!("board saved to {}", path),
+        Err(e) => log::error!("Failed to save the board: {}", e),
     }
 }
 
 fn setup_board(
     mut commands: Commands,
-    mut state: ResMut<NextState<AppState>>,
     asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    options: Res<cli::Options>,
+    challenge: Option<ResMut<daily::DailyChallenge>>,
+    puzzle: Option<ResMut<puzzle::PuzzleChallenge>>,
 ) {
-    commands.insert_resource(
-        BoardOptions{
-            map_size:(20,20),
-            bomb_count: 40,
-            tile_padding: 1.,
-            safe_start: true,
-            ..Default::default()
-        }
-    );
+    let mut board_options = settings.board.clone();
+    options.apply(&mut board_options);
+    if let Some(mut challenge) = challenge {
+        challenge.apply(&mut board_options);
+    }
+    if let Some(mut puzzle) = puzzle {
+        puzzle.apply(&mut board_options);
+    }
+    commands.insert_resource(board_options);
     commands.insert_resource(
         BoardAssets {
             label: "Default".to_string(),
@@ -118,11 +340,27 @@ fn setup_board(
                 texture: asset_server.load("sprites/flag.png"),
                 color: Color::WHITE
             },
+            question_material: SpriteMaterial {
+                texture: asset_server.load("sprites/question.png"),
+                color: Color::WHITE
+            },
             bomb_material: SpriteMaterial {
                 texture: asset_server.load("sprites/bomb.png"),
                 color: Color::WHITE
             },
+            atlas: None,
+            sounds: BoardSounds {
+                reveal: Some(asset_server.load("sounds/reveal.wav")),
+                cascade: Some(asset_server.load("sounds/cascade.wav")),
+                flag: Some(asset_server.load("sounds/flag.wav")),
+                unflag: Some(asset_server.load("sounds/unflag.wav")),
+                chord: Some(asset_server.load("sounds/chord.wav")),
+                explosion: Some(asset_server.load("sounds/explosion.wav")),
+                win: Some(asset_server.load("sounds/win.wav")),
+                tick: Some(asset_server.load("sounds/tick.wav")),
+            },
         }
     );
-    state.set(AppState::InGame);
+    // Replaces the default assets once loaded
+    commands.insert_resource(BoardTheme(asset_server.load(options.theme.clone())));
 }
@@ -0,0 +1,572 @@
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::components::{KeyBindings, Player};
+use board_plugin::net::{ClientMessage, NetClient, DEFAULT_PORT};
+use board_plugin::resources::{
+    BoardOptions, BoardPlayer, BoardPlayers, BoardTheme, GameMode, GameStatus, GameTimer, Theme,
+};
+
+use crate::daily::DailyChallenge;
+use crate::online::server_race_outcome;
+use crate::puzzle::{PuzzleChallenge, PuzzleChoice, PUZZLES};
+use crate::{AppState, GamePhase, ThemeChoice, THEMES};
+
+/// Board difficulty presets
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [
+        Difficulty::Beginner,
+        Difficulty::Intermediate,
+        Difficulty::Expert,
+    ];
+
+    /// Board size and bomb count of the preset
+    pub const fn board(&self) -> ((u16, u16), u16) {
+        match self {
+            Difficulty::Beginner => ((9, 9), 10),
+            Difficulty::Intermediate => ((16, 16), 40),
+            Difficulty::Expert => ((30, 16), 99),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let ((width, height), bombs) = self.board();
+        format!("{:?} {}x{}, {} mines", self, width, height, bombs)
+    }
+}
+
+/// Custom board settings, edited by the custom board dialog
+#[derive(Debug, Clone, Resource)]
+pub struct CustomBoard {
+    pub width: u16,
+    pub height: u16,
+    pub bomb_count: u16,
+}
+
+impl Default for CustomBoard {
+    fn default() -> Self {
+        Self {
+            width: 20,
+            height: 20,
+            bomb_count: 40,
+        }
+    }
+}
+
+impl CustomBoard {
+    pub const MIN_SIDE: u16 = 2;
+    /// Largest side of a board, drawn in chunks so only the visible part is spawned
+    pub const MAX_SIDE: u16 = 500;
+
+    /// Checks the board can be generated, the safe start needing a bomb free 3x3 square
+    pub fn validate(&self, safe_start: bool) -> Result<(), String> {
+        let sides = Self::MIN_SIDE..=Self::MAX_SIDE;
+        if !sides.contains(&self.width) || !sides.contains(&self.height) {
+            return Err(format!(
+                "Width and height must be between {} and {}",
+                Self::MIN_SIDE,
+                Self::MAX_SIDE
+            ));
+        }
+        let reserved = if safe_start { 9 } else { 1 };
+        let tiles = self.width as u32 * self.height as u32;
+        if tiles <= reserved {
+            let reason = if safe_start { " besides the safe start" } else { "" };
+            return Err(format!("A {}x{} board is too small for a mine{}", self.width, self.height, reason));
+        }
+        let max_bombs = (tiles - reserved).min(u16::MAX as u32);
+        if self.bomb_count == 0 {
+            return Err("At least 1 mine is needed".to_string());
+        }
+        if self.bomb_count as u32 > max_bombs {
+            return Err(format!("At most {} mines fit on this board", max_bombs));
+        }
+        Ok(())
+    }
+}
+
+/// Custom board dialog field
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CustomField {
+    Width,
+    Height,
+    Bombs,
+}
+
+/// Menu button action
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Component)]
+pub enum MenuAction {
+    Play(Difficulty),
+    /// Plays the board of the day, ranked once
+    Daily,
+    /// Plays the next puzzle of `PUZZLES`
+    Puzzle,
+    CustomBoard,
+    Endless,
+    /// Joins the game server running on this machine
+    JoinServer,
+    /// Watches the first player of the game server running on this machine
+    Spectate,
+    NextTheme,
+    /// Switches between a single player game and a two players race
+    TogglePlayers,
+    /// Changes a custom board field, by ten with Shift held
+    Step(CustomField, i32),
+    StartCustom,
+    /// Restarts the game, or asks the server for a new round
+    PlayAgain,
+    MainMenu,
+}
+
+/// Root of a menu screen, despawned when leaving it
+#[derive(Debug, Copy, Clone, Component)]
+pub struct MenuRoot;
+
+/// Text of the selected theme
+#[derive(Debug, Copy, Clone, Component)]
+pub struct ThemeText;
+
+/// Text of the player count
+#[derive(Debug, Copy, Clone, Component)]
+pub struct PlayersText;
+
+/// Value text of a custom board field
+#[derive(Debug, Copy, Clone, Component)]
+pub struct CustomFieldText(CustomField);
+
+/// Validation error text of the custom board dialog
+#[derive(Debug, Copy, Clone, Component)]
+pub struct CustomErrorText;
+
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.3);
+const HOVERED_COLOR: Color = Color::srgb(0.35, 0.35, 0.45);
+const PRESSED_COLOR: Color = Color::srgb(0.2, 0.45, 0.3);
+
+/// Main menu, custom board dialog and game over screen
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CustomBoard>();
+        app.add_systems(OnEnter(AppState::MainMenu), spawn_main_menu);
+        app.add_systems(OnExit(AppState::MainMenu), despawn_menu);
+        app.add_systems(OnEnter(AppState::CustomBoard), spawn_custom_dialog);
+        app.add_systems(OnExit(AppState::CustomBoard), despawn_menu);
+        app.add_systems(OnEnter(GamePhase::Over), spawn_game_over);
+        app.add_systems(OnExit(GamePhase::Over), despawn_menu);
+        app.add_systems(Update, (
+            button_colors,
+            menu_actions,
+            update_theme_text,
+            update_players_text,
+            update_custom_dialog.run_if(in_state(AppState::CustomBoard)),
+        ));
+    }
+}
+
+fn menu_font(asset_server: &AssetServer) -> Handle<Font> {
+    asset_server.load("fonts/pixeled.ttf")
+}
+
+fn text(value: impl Into<String>, font: &Handle<Font>, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: font.clone(),
+            font_size,
+            color: Color::WHITE,
+        },
+    )
+}
+
+fn spawn_button(parent: &mut ChildBuilder, font: &Handle<Font>, label: impl Into<String>, action: MenuAction) {
+    spawn_button_with(parent, font, label, action, ());
+}
+
+/// Spawns a button, with `marker` on its label to update it
+fn spawn_button_with(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: impl Into<String>,
+    action: MenuAction,
+    marker: impl Bundle,
+) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                padding: UiRect::axes(Val::Px(16.), Val::Px(8.)),
+                margin: UiRect::all(Val::Px(4.)),
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            background_color: BUTTON_COLOR.into(),
+            ..Default::default()
+        })
+        .insert(action)
+        .with_children(|parent| {
+            parent.spawn(text(label, font, 14.)).insert(marker);
+        });
+}
+
+/// Full screen column holding a menu screen
+fn menu_root(background: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        },
+        background_color: background.into(),
+        z_index: ZIndex::Global(10),
+        ..Default::default()
+    }
+}
+
+fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = menu_font(&asset_server);
+    commands
+        .spawn(menu_root(Color::srgb(0.1, 0.1, 0.12)))
+        .insert(Name::new("Main Menu"))
+        .insert(MenuRoot)
+        .with_children(|parent| {
+            parent.spawn(text("Mine Sweeper!", &font, 32.));
+            for difficulty in Difficulty::ALL {
+                spawn_button(parent, &font, difficulty.label(), MenuAction::Play(difficulty));
+            }
+            spawn_button(parent, &font, "Daily challenge", MenuAction::Daily);
+            spawn_button(parent, &font, "Puzzle", MenuAction::Puzzle);
+            spawn_button(parent, &font, "Custom board", MenuAction::CustomBoard);
+            spawn_button(parent, &font, "Endless", MenuAction::Endless);
+            spawn_button(parent, &font, "Join local server", MenuAction::JoinServer);
+            spawn_button(parent, &font, "Spectate local server", MenuAction::Spectate);
+            spawn_button_with(parent, &font, "Players", MenuAction::TogglePlayers, PlayersText);
+            spawn_button_with(parent, &font, "Theme", MenuAction::NextTheme, ThemeText);
+        });
+}
+
+fn spawn_custom_dialog(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = menu_font(&asset_server);
+    let fields = [
+        (CustomField::Width, "Width"),
+        (CustomField::Height, "Height"),
+        (CustomField::Bombs, "Mines"),
+    ];
+    commands
+        .spawn(menu_root(Color::srgb(0.1, 0.1, 0.12)))
+        .insert(Name::new("Custom Board Dialog"))
+        .insert(MenuRoot)
+        .with_children(|parent| {
+            parent.spawn(text("Custom board", &font, 24.));
+            for (field, label) in fields {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(text(label, &font, 14.)).insert(Style {
+                            width: Val::Px(120.),
+                            ..Default::default()
+                        });
+                        spawn_button(parent, &font, "-", MenuAction::Step(field, -1));
+                        parent
+                            .spawn(text("", &font, 14.))
+                            .insert(Style {
+                                width: Val::Px(80.),
+                                justify_content: JustifyContent::Center,
+                                ..Default::default()
+                            })
+                            .insert(CustomFieldText(field));
+                        spawn_button(parent, &font, "+", MenuAction::Step(field, 1));
+                    });
+            }
+            parent.spawn(text("Hold Shift to change by 10", &font, 10.));
+            parent
+                .spawn(text("", &font, 12.))
+                .insert(CustomErrorText);
+            spawn_button(parent, &font, "Start", MenuAction::StartCustom);
+            spawn_button(parent, &font, "Back", MenuAction::MainMenu);
+        });
+}
+
+/// Players with their game status and elapsed seconds
+pub type BoardResults<'w, 's> = Query<'w, 's, (&'static Player, &'static GameStatus, &'static GameTimer)>;
+
+/// Message of the game over screen, `None` while the game goes on.
+/// A race is won by the first player clearing their board, or by the last one
+/// who didn't explode
+pub fn game_outcome(boards: &BoardResults, client: Option<&NetClient>) -> Option<String> {
+    if let Some(outcome) = client.and_then(server_race_outcome) {
+        return Some(outcome);
+    }
+    let mut results: Vec<_> = boards.iter().collect();
+    results.sort_by_key(|(player, ..)| player.index);
+    let playing: Vec<_> = results
+        .iter()
+        .filter(|(_, status, _)| **status == GameStatus::Playing)
+        .collect();
+    let winner = results
+        .iter()
+        .filter(|(_, status, _)| **status == GameStatus::Won)
+        .min_by_key(|(_, _, timer)| timer.elapsed);
+    match results.as_slice() {
+        [] => None,
+        [(_, GameStatus::Playing, _)] => None,
+        [(_, GameStatus::Won, timer)] => Some(format!("Board cleared in {} seconds!", timer.seconds())),
+        [(_, GameStatus::Lost, _)] => Some("Boom! Game over".to_string()),
+        _ => match (winner, playing.as_slice()) {
+            (Some((player, _, timer)), _) => {
+                Some(format!("{} wins in {} seconds!", player.name, timer.seconds()))
+            }
+            (None, []) => Some("Everyone exploded, draw!".to_string()),
+            (None, [(player, ..)]) => Some(format!("{} wins, last one standing!", player.name)),
+            (None, _) => None,
+        },
+    }
+}
+
+fn spawn_game_over(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    boards: BoardResults,
+    client: Option<Res<NetClient>>,
+    board_options: Res<BoardOptions>,
+) {
+    let font = menu_font(&asset_server);
+    let message = match (board_options.mode, game_outcome(&boards, client.as_deref())) {
+        // Puzzles aren't timed
+        (GameMode::Puzzle, Some(_)) if boards.iter().any(|(_, s, _)| *s == GameStatus::Won) => {
+            "Puzzle solved!".to_string()
+        }
+        (GameMode::Puzzle, Some(_)) => "Wrong reveal, puzzle failed".to_string(),
+        (_, outcome) => outcome.unwrap_or_default(),
+    };
+    // Transparent root, so the board stays visible around the panel
+    commands
+        .spawn(menu_root(Color::NONE))
+        .insert(Name::new("Game Over"))
+        .insert(MenuRoot)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(16.)),
+                        ..Default::default()
+                    },
+                    background_color: Color::srgba(0., 0., 0., 0.8).into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(text(message, &font, 18.));
+                    spawn_button(parent, &font, "Play again", MenuAction::PlayAgain);
+                    spawn_button(parent, &font, "Change difficulty", MenuAction::MainMenu);
+                });
+        });
+}
+
+fn despawn_menu(mut commands: Commands, roots: Query<Entity, With<MenuRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+type ChangedButtons = (Changed<Interaction>, With<MenuAction>);
+
+fn button_colors(
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), ChangedButtons>,
+) {
+    for (interaction, mut color) in buttons.iter_mut() {
+        *color = match interaction {
+            Interaction::Pressed => PRESSED_COLOR,
+            Interaction::Hovered => HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_actions(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &MenuAction), Changed<Interaction>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut board_options: ResMut<BoardOptions>,
+    mut custom_board: ResMut<CustomBoard>,
+    mut theme_choice: ResMut<ThemeChoice>,
+    mut board_players: ResMut<BoardPlayers>,
+    mut puzzle_choice: ResMut<PuzzleChoice>,
+    mut client: Option<ResMut<NetClient>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, action) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        log::debug!("menu action {:?}", action);
+        match *action {
+            MenuAction::Play(difficulty) => {
+                let (map_size, bomb_count) = difficulty.board();
+                board_options.map_size = map_size;
+                board_options.bomb_count = bomb_count;
+                next_state.set(AppState::Loading);
+            }
+            MenuAction::Daily => {
+                let mut challenge = DailyChallenge::today();
+                challenge.apply(&mut board_options);
+                commands.insert_resource(challenge);
+                // Ranked games are played alone
+                *board_players = BoardPlayers::default();
+                next_state.set(AppState::Loading);
+            }
+            MenuAction::Puzzle => {
+                let path = PUZZLES[puzzle_choice.0 % PUZZLES.len()];
+                puzzle_choice.0 = (puzzle_choice.0 + 1) % PUZZLES.len();
+                match PuzzleChallenge::load(path) {
+                    Ok(mut puzzle) => {
+                        puzzle.apply(&mut board_options);
+                        commands.insert_resource(puzzle);
+                        next_state.set(AppState::Loading);
+                    }
+                    Err(e) => log::error!("Failed to load the puzzle {}: {}", path, e),
+                }
+            }
+            MenuAction::CustomBoard => next_state.set(AppState::CustomBoard),
+            MenuAction::Endless => next_state.set(AppState::Endless),
+            MenuAction::JoinServer => {
+                match NetClient::connect(("127.0.0.1", DEFAULT_PORT), "Player") {
+                    Ok(client) => {
+                        commands.insert_resource(client);
+                        next_state.set(AppState::Connecting);
+                    }
+                    Err(e) => log::error!("Failed to join the server: {}", e),
+                }
+            }
+            MenuAction::Spectate => {
+                match NetClient::spectate(("127.0.0.1", DEFAULT_PORT), 0) {
+                    Ok(client) => {
+                        commands.insert_resource(client);
+                        next_state.set(AppState::Connecting);
+                    }
+                    Err(e) => log::error!("Failed to spectate the server: {}", e),
+                }
+            }
+            MenuAction::TogglePlayers => {
+                *board_players = match board_players.0.len() {
+                    1 => BoardPlayers(vec![
+                        BoardPlayer {
+                            name: "Player 1".to_string(),
+                            keys: KeyBindings::wasd(),
+                        },
+                        BoardPlayer {
+                            name: "Player 2".to_string(),
+                            keys: KeyBindings::arrows(),
+                        },
+                    ]),
+                    _ => BoardPlayers::default(),
+                };
+            }
+            MenuAction::NextTheme => {
+                theme_choice.0 = (theme_choice.0 + 1) % THEMES.len();
+                commands.insert_resource(BoardTheme(asset_server.load(THEMES[theme_choice.0])));
+            }
+            MenuAction::Step(field, delta) => {
+                let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+                let delta = if shift { delta * 10 } else { delta };
+                let value = match field {
+                    CustomField::Width => &mut custom_board.width,
+                    CustomField::Height => &mut custom_board.height,
+                    CustomField::Bombs => &mut custom_board.bomb_count,
+                };
+                *value = (*value as i32 + delta).clamp(0, u16::MAX as i32) as u16;
+            }
+            MenuAction::StartCustom => {
+                match custom_board.validate(board_options.safe_start) {
+                    Ok(()) => {
+                        board_options.map_size = (custom_board.width, custom_board.height);
+                        board_options.bomb_count = custom_board.bomb_count;
+                        next_state.set(AppState::Loading);
+                    }
+                    Err(e) => log::info!("invalid custom board: {}", e),
+                }
+            }
+            // The board is respawned once the server started the round
+            MenuAction::PlayAgain => match client.as_mut() {
+                Some(client) => client.send(&ClientMessage::NewGame),
+                None => next_state.set(AppState::Loading),
+            },
+            MenuAction::MainMenu => next_state.set(AppState::MainMenu),
+        }
+    }
+}
+
+fn update_theme_text(
+    theme_choice: Res<ThemeChoice>,
+    board_theme: Option<Res<BoardTheme>>,
+    themes: Res<Assets<Theme>>,
+    mut texts: Query<&mut Text, With<ThemeText>>,
+) {
+    let label = board_theme
+        .and_then(|t| themes.get(&t.0))
+        .map_or(THEMES[theme_choice.0], |t| t.assets.label.as_str());
+    for mut text in texts.iter_mut() {
+        let value = format!("Theme: {}", label);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+fn update_players_text(
+    board_players: Res<BoardPlayers>,
+    mut texts: Query<&mut Text, With<PlayersText>>,
+) {
+    let value = match board_players.0.len() {
+        1 => "Players: 1".to_string(),
+        n => format!("Players: {} (race, WASD + Space/E, arrows + Enter/RShift)", n),
+    };
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn update_custom_dialog(
+    custom_board: Res<CustomBoard>,
+    board_options: Res<BoardOptions>,
+    mut fields: Query<(&mut Text, &CustomFieldText), Without<CustomErrorText>>,
+    mut errors: Query<&mut Text, With<CustomErrorText>>,
+    added: Query<(), Added<CustomFieldText>>,
+) {
+    if !custom_board.is_changed() && added.is_empty() {
+        return;
+    }
+    for (mut text, field) in fields.iter_mut() {
+        text.sections[0].value = match field.0 {
+            CustomField::Width => custom_board.width,
+            CustomField::Height => custom_board.height,
+            CustomField::Bombs => custom_board.bomb_count,
+        }
+        .to_string();
+    }
+    let error = custom_board.validate(board_options.safe_start).err().unwrap_or_default();
+    for mut text in errors.iter_mut() {
+        text.sections[0].value = error.clone();
+        text.sections[0].style.color = Color::srgb(1., 0.4, 0.4);
+    }
+}
@@ -0,0 +1,243 @@
+use std::io;
+
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::components::{RemoteBoard, TileCursor};
+use board_plugin::formats;
+use board_plugin::net::{NetClient, NetMode, DEFAULT_PORT};
+use board_plugin::resources::GameStatus;
+
+use crate::cli::Options;
+use crate::AppState;
+
+/// Scoreboard text of a server game
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Scoreboard;
+
+/// Move timings panel of a spectator
+#[derive(Debug, Copy, Clone, Component)]
+pub struct MoveTimings;
+
+/// Is the overlay of a spectator shown, with the player cursor and the move timings.
+/// Toggled with the C key
+#[derive(Debug, Copy, Clone, Resource)]
+pub struct SpectatorOverlay(pub bool);
+
+impl Default for SpectatorOverlay {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Moves listed by the move timings panel
+const SHOWN_MOVES: usize = 10;
+
+/// Games played on a game server: connection, rounds and scoreboard
+pub struct OnlinePlugin;
+
+impl Plugin for OnlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpectatorOverlay>();
+        app.add_systems(OnEnter(AppState::MainMenu), disconnect);
+        app.add_systems(Update, wait_for_welcome.run_if(in_state(AppState::Connecting)));
+        app.add_systems(OnEnter(AppState::InGame), spawn_scoreboard.run_if(resource_exists::<NetClient>));
+        app.add_systems(OnExit(AppState::InGame), despawn_scoreboard);
+        app.add_systems(Update, (
+            follow_rounds,
+            update_scoreboard,
+            toggle_overlay,
+            update_overlay,
+        ).run_if(in_state(AppState::InGame)).run_if(resource_exists::<NetClient>));
+    }
+}
+
+/// Spectator from the command line: `--spectate [player]` watches a player of the local
+/// server by scoreboard index, `--replay <file>` follows a replay file of the server or
+/// plays back a `.rmv` replay
+pub fn spectator(options: &Options) -> Option<io::Result<NetClient>> {
+    if let Some(player) = options.spectate {
+        return Some(NetClient::spectate(("127.0.0.1", DEFAULT_PORT), player));
+    }
+    let path = options.replay.as_ref()?;
+    let rmv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("rmv"));
+    Some(match rmv {
+        true => formats::load_rmv(path)
+            .map(|replay| NetClient::replay(&replay))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        false => NetClient::tail(path),
+    })
+}
+
+/// Leaves the server when back to the main menu
+fn disconnect(mut commands: Commands) {
+    commands.remove_resource::<NetClient>();
+}
+
+/// Starts the game once the server sent the round
+fn wait_for_welcome(mut client: ResMut<NetClient>, mut next_state: ResMut<NextState<AppState>>) {
+    client.receive();
+    if client.welcome.is_some() {
+        next_state.set(AppState::Loading);
+    } else if client.disconnected {
+        log::error!("Server closed the connection");
+        next_state.set(AppState::MainMenu);
+    }
+}
+
+/// Respawns the board when the server starts a new round, and leaves when disconnected
+fn follow_rounds(
+    client: Res<NetClient>,
+    boards: Query<&RemoteBoard>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if client.disconnected {
+        log::error!("Connection to the server lost");
+        next_state.set(AppState::MainMenu);
+        return;
+    }
+    let Some(welcome) = &client.welcome else {
+        return;
+    };
+    if boards.iter().any(|b| b.round != welcome.round) {
+        log::info!("Round {} started", welcome.round);
+        next_state.set(AppState::Loading);
+    }
+}
+
+/// Message of the game over screen for a race on a server, won by the fastest
+/// player clearing their board
+pub fn server_race_outcome(client: &NetClient) -> Option<String> {
+    let welcome = client.welcome.as_ref()?;
+    if welcome.mode != NetMode::Race {
+        return None;
+    }
+    let winner = client
+        .players
+        .iter()
+        .filter(|p| p.status == GameStatus::Won)
+        .min_by_key(|p| p.seconds)?;
+    Some(format!("{} wins in {} seconds!", winner.name, winner.seconds))
+}
+
+fn spawn_scoreboard(mut commands: Commands, asset_server: Res<AssetServer>, client: Res<NetClient>) {
+    let font = asset_server.load("fonts/pixeled.ttf");
+    if client.spectator {
+        commands
+            .spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 12.,
+                    color: Color::WHITE,
+                },
+            ).with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.),
+                right: Val::Px(8.),
+                ..Default::default()
+            }))
+            .insert(Name::new("Move Timings"))
+            .insert(Scoreboard)
+            .insert(MoveTimings);
+    }
+    commands
+        .spawn(TextBundle::from_section(
+            "",
+            TextStyle {
+                font,
+                font_size: 12.,
+                color: Color::WHITE,
+            },
+        ).with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.),
+            left: Val::Px(8.),
+            ..Default::default()
+        }))
+        .insert(Name::new("Scoreboard"))
+        .insert(Scoreboard);
+}
+
+fn despawn_scoreboard(mut commands: Commands, scoreboards: Query<Entity, With<Scoreboard>>) {
+    for entity in scoreboards.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_scoreboard(
+    client: Res<NetClient>,
+    mut texts: Query<&mut Text, (With<Scoreboard>, Without<MoveTimings>)>,
+) {
+    if !client.is_changed() {
+        return;
+    }
+    let Some(welcome) = &client.welcome else {
+        return;
+    };
+    let mut value = format!("{:?} round {}", welcome.mode, welcome.round + 1);
+    for (index, player) in client.players.iter().enumerate() {
+        let you = match (index == welcome.player, client.spectator) {
+            (false, _) => "",
+            (true, false) => " (you)",
+            (true, true) => " (watched)",
+        };
+        let status = match player.status {
+            GameStatus::Playing => format!("{} tiles left", player.tiles_left),
+            GameStatus::Won => "cleared".to_string(),
+            GameStatus::Lost => "exploded".to_string(),
+        };
+        value.push_str(&format!("\n{}{}: {}, {}s", player.name, you, status, player.seconds));
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn toggle_overlay(mut overlay: ResMut<SpectatorOverlay>, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::KeyC) {
+        overlay.0 = !overlay.0;
+        log::info!("spectator overlay: {}", overlay.0);
+    }
+}
+
+/// Shows the watched player cursor and lists their last moves, with the time since
+/// the game start and since the previous move
+fn update_overlay(
+    client: Res<NetClient>,
+    overlay: Res<SpectatorOverlay>,
+    mut cursors: Query<&mut Visibility, With<TileCursor>>,
+    mut texts: Query<&mut Text, With<MoveTimings>>,
+) {
+    if !client.spectator {
+        return;
+    }
+    let visibility = if overlay.0 { Visibility::Inherited } else { Visibility::Hidden };
+    for mut cursor in cursors.iter_mut() {
+        cursor.set_if_neq(visibility);
+    }
+    let Some(welcome) = &client.welcome else {
+        return;
+    };
+    let moves: Vec<_> = client.moves.iter().filter(|m| m.player == welcome.player).collect();
+    let mut value = String::new();
+    if overlay.0 {
+        let first = moves.len().saturating_sub(SHOWN_MOVES);
+        for (index, timing) in moves.iter().enumerate().skip(first) {
+            let previous = index.checked_sub(1).map_or(0, |i| moves[i].millis);
+            let action = if timing.mark { "mark" } else { "open" };
+            value.push_str(&format!(
+                "#{} {} {} {:.1}s (+{:.1}s)\n",
+                index + 1,
+                action,
+                timing.coordinates,
+                timing.millis as f32 / 1000.,
+                timing.millis.saturating_sub(previous) as f32 / 1000.,
+            ));
+        }
+    }
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+
+use bevy::log;
+use bevy::prelude::*;
+use board_plugin::formats::{self, FormatError};
+use board_plugin::resources::{BoardOptions, GameMode};
+use board_plugin::solver;
+
+use crate::cli::Options;
+use crate::settings::{leave_challenge, Challenge};
+use crate::AppState;
+
+/// Puzzle files, cycled by the main menu puzzle button
+pub const PUZZLES: [&str; 3] = [
+    "assets/puzzles/small.txt",
+    "assets/puzzles/medium.txt",
+    "assets/puzzles/large.txt",
+];
+
+/// Index of the next puzzle in `PUZZLES`
+#[derive(Debug, Copy, Clone, Default, Resource)]
+pub struct PuzzleChoice(pub usize);
+
+/// Puzzle being played: a pre-revealed layout to clear without guessing, lost on the
+/// first wrong reveal
+#[derive(Debug, Clone, Resource)]
+pub struct PuzzleChallenge {
+    pub path: PathBuf,
+    /// Board options to restore when leaving the puzzle
+    options: Option<BoardOptions>,
+}
+
+impl PuzzleChallenge {
+    /// Reads the puzzle, warning when it can't be solved without guessing
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FormatError> {
+        let path = path.as_ref();
+        let layout = formats::load_layout(path)?;
+        if !solver::is_solvable(&layout.into_board()) {
+            log::warn!("Puzzle {} can't be solved without guessing", path.display());
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            options: None,
+        })
+    }
+
+    /// Replaces the board layout and mode with the puzzle ones
+    pub fn apply(&mut self, board_options: &mut BoardOptions) {
+        self.options = Some(board_options.clone());
+        board_options.layout = Some(self.path.clone());
+        board_options.mode = GameMode::Puzzle;
+    }
+}
+
+/// Writes a puzzle generated from the board options, with a comment header
+pub fn generate(options: &Options, path: &Path) -> Result<(), FormatError> {
+    let board_options = BoardOptions {
+        map_size: options.map_size,
+        bomb_count: options.bomb_count,
+        ..Default::default()
+    };
+    let seed = options.seed.unwrap_or_else(rand::random);
+    // A few bombs are given away as flags
+    let flags = (options.bomb_count as usize / 10).max(1);
+    let (width, height) = options.map_size;
+    let layout = solver::generate_puzzle(&board_options, seed, flags).ok_or(FormatError::InvalidSize {
+        width,
+        height,
+        bombs: options.bomb_count,
+    })?;
+    let content = format!(
+        "# Puzzle {}x{} with {} mines, seed {}: uncover every safe tile without guessing\n{}",
+        width, height, options.bomb_count, seed, layout
+    );
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+impl Challenge for PuzzleChallenge {
+    fn user_options(&self) -> Option<&BoardOptions> {
+        self.options.as_ref()
+    }
+}
+
+/// Puzzle mode: solver checked layouts, untimed and lost on a wrong reveal
+pub struct PuzzlePlugin;
+
+impl Plugin for PuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PuzzleChoice>();
+        app.add_systems(OnEnter(AppState::MainMenu), leave_challenge::<PuzzleChallenge>);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(bevy::state::app::StatesPlugin)
+            .init_state::<AppState>()
+            .add_plugins(PuzzlePlugin)
+            .add_systems(Startup, |mut commands: Commands| {
+                commands.insert_resource(BoardOptions::default());
+            });
+        app
+    }
+
+    #[test]
+    fn leaving_restores_the_user_board() {
+        let mut puzzle = PuzzleChallenge {
+            path: PUZZLES[0].into(),
+            options: None,
+        };
+        let mut user_options = BoardOptions {
+            map_size: (12, 34),
+            ..Default::default()
+        };
+        puzzle.apply(&mut user_options);
+        let mut app = puzzle_app();
+        app.insert_resource(puzzle);
+        // Entering the main menu on startup, before the board options exist
+        app.update();
+        assert!(app.world().contains_resource::<PuzzleChallenge>());
+
+        app.world_mut().insert_resource(user_options);
+        app.world_mut().resource_mut::<NextState<AppState>>().set(AppState::InGame);
+        app.update();
+        app.world_mut().resource_mut::<NextState<AppState>>().set(AppState::MainMenu);
+        app.update();
+        let board_options = app.world().resource::<BoardOptions>();
+        assert_eq!((board_options.map_size, board_options.layout.clone()), ((12, 34), None));
+        assert!(!app.world().contains_resource::<PuzzleChallenge>());
+    }
+}
@@ -0,0 +1,264 @@
+use std::path::PathBuf;
+
+use bevy::log;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowMoved, WindowResized};
+use board_plugin::resources::{BoardOptions, BoardPlayers, SoundOptions};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::check_board;
+use crate::daily::DailyChallenge;
+use crate::puzzle::PuzzleChallenge;
+use crate::{ThemeChoice, THEMES};
+
+/// Window size and position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub size: (f32, f32),
+    /// Position on the screen, chosen by the system when `None`
+    pub position: Option<(i32, i32)>,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        Self {
+            size: (700., 800.),
+            position: None,
+        }
+    }
+}
+
+/// User settings, loaded on startup from `settings.ron` in the platform config directory
+/// and saved there when they change. Missing fields keep their default
+#[derive(Debug, Clone, Serialize, Deserialize, Resource)]
+#[serde(default)]
+pub struct Settings {
+    /// Board options. The board size and mines follow the menu, the other options
+    /// are only edited in the file, such as the animation and question mark toggles
+    pub board: BoardOptions,
+    /// Theme asset path
+    pub theme: String,
+    /// Players and their key bindings
+    pub players: BoardPlayers,
+    pub sound: SoundOptions,
+    pub window: WindowGeometry,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            board: BoardOptions {
+                map_size: (20, 20),
+                bomb_count: 40,
+                tile_padding: 1.,
+                safe_start: true,
+                ..Default::default()
+            },
+            theme: THEMES[0].to_string(),
+            players: Default::default(),
+            sound: Default::default(),
+            window: Default::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Settings file: `$XDG_CONFIG_HOME` or `~/.config` on Linux, `~/Library/Application
+    /// Support` on macOS and `%APPDATA%` on Windows
+    pub fn path() -> Option<PathBuf> {
+        let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+        let config = if cfg!(target_os = "windows") {
+            var("APPDATA")
+        } else if cfg!(target_os = "macos") {
+            var("HOME").map(|home| home.join("Library/Application Support"))
+        } else {
+            var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))
+        };
+        Some(config?.join("mine_sweeper").join("settings.ron"))
+    }
+
+    /// Reads the settings file, or the default settings if it can't be read. Returns the
+    /// warnings to log once the log plugin is installed
+    pub fn load() -> (Self, Vec<String>) {
+        let Some(path) = Self::path() else {
+            return (Self::default(), Vec::new());
+        };
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(content) => ron::from_str(&content).map_err(|e| e.to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return (Self::default(), Vec::new()),
+            Err(e) => Err(e.to_string()),
+        };
+        let mut warnings = Vec::new();
+        let mut settings = settings.unwrap_or_else(|e| {
+            warnings.push(format!("Failed to read {}: {}", path.display(), e));
+            Self::default()
+        });
+        let board = &settings.board;
+        if let Err(e) = check_board(board.map_size, board.bomb_count, board.safe_start) {
+            warnings.push(format!("Invalid board in {}: {}, using the default board", path.display(), e));
+            settings.board = Self::default().board;
+        }
+        (settings, warnings)
+    }
+
+    /// Writes the settings file
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("No config directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = ron::ser::to_string_pretty(self, Default::default()).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| e.to_string())
+    }
+}
+
+/// Game mode replacing the board options of the user while played
+pub trait Challenge: Resource {
+    /// Board options of the user, restored when leaving the challenge
+    fn user_options(&self) -> Option<&BoardOptions>;
+}
+
+/// Restores the board options of the user when back to the main menu. The main menu
+/// is entered on startup, before the board options are inserted
+pub fn leave_challenge<C: Challenge>(
+    mut commands: Commands,
+    challenge: Option<Res<C>>,
+    board_options: Option<ResMut<BoardOptions>>,
+) {
+    let (Some(challenge), Some(mut board_options)) = (challenge, board_options) else {
+        return;
+    };
+    if let Some(options) = challenge.user_options() {
+        *board_options = options.clone();
+    }
+    commands.remove_resource::<C>();
+}
+
+/// Keeps the settings up to date and saves them when they change
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (follow_settings, save_settings).chain());
+    }
+}
+
+/// Board size and mines picked in the menu, the only board options saved
+type MenuBoard = ((u16, u16), u16);
+
+/// Copies the board picked in the menu and the changed theme, players, sound and window
+/// into the settings. The board options of a daily challenge or a puzzle aren't the
+/// user's, nor the first ones seen, which hold the command line overrides
+#[allow(clippy::too_many_arguments)]
+fn follow_settings(
+    mut settings: ResMut<Settings>,
+    board_options: Option<Res<BoardOptions>>,
+    challenge: Option<Res<DailyChallenge>>,
+    puzzle: Option<Res<PuzzleChallenge>>,
+    theme_choice: Res<ThemeChoice>,
+    players: Res<BoardPlayers>,
+    sound: Res<SoundOptions>,
+    mut resized: EventReader<WindowResized>,
+    mut moved: EventReader<WindowMoved>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut seen_board: Local<Option<MenuBoard>>,
+) {
+    let changed = |r: &dyn DetectChanges| r.is_changed() && !r.is_added();
+    let own_board = challenge.is_none() && puzzle.is_none();
+    if let Some(board_options) = board_options.filter(|_| own_board) {
+        let board = (board_options.map_size, board_options.bomb_count);
+        if seen_board.is_some_and(|seen| seen != board) {
+            (settings.board.map_size, settings.board.bomb_count) = board;
+        }
+        *seen_board = Some(board);
+    }
+    if changed(&theme_choice) {
+        settings.theme = THEMES[theme_choice.0].to_string();
+    }
+    if changed(&players) {
+        settings.players = players.clone();
+    }
+    if changed(&sound) {
+        settings.sound = sound.clone();
+    }
+    let primary = windows.get_single().ok();
+    for event in resized.read().filter(|e| Some(e.window) == primary) {
+        let size = (event.width, event.height);
+        if settings.window.size != size {
+            settings.window.size = size;
+        }
+    }
+    for event in moved.read().filter(|e| Some(e.window) == primary) {
+        let position = Some((event.position.x, event.position.y));
+        if settings.window.position != position {
+            settings.window.position = position;
+        }
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        if let Err(e) = settings.save() {
+            log::error!("Failed to save the settings: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings_app(board_options: BoardOptions) -> App {
+        let mut app = App::new();
+        app.add_event::<WindowResized>()
+            .add_event::<WindowMoved>()
+            .insert_resource(Settings::default())
+            .insert_resource(board_options)
+            .insert_resource(ThemeChoice(0))
+            .init_resource::<BoardPlayers>()
+            .init_resource::<SoundOptions>()
+            .add_systems(Update, follow_settings);
+        app
+    }
+
+    #[test]
+    fn command_line_and_theme_options_are_not_saved() {
+        let command_line = BoardOptions {
+            map_size: (300, 9),
+            bomb_count: 99,
+            ..Settings::default().board
+        };
+        let mut app = settings_app(command_line);
+        app.update();
+        // A theme padding
+        app.world_mut().resource_mut::<BoardOptions>().tile_padding = 3.;
+        app.update();
+        let settings = app.world().resource::<Settings>();
+        assert_eq!(settings.board.map_size, Settings::default().board.map_size);
+        assert_eq!(settings.board.tile_padding, Settings::default().board.tile_padding);
+    }
+
+    #[test]
+    fn menu_board_is_saved() {
+        let mut app = settings_app(Settings::default().board);
+        app.update();
+        let mut board_options = app.world_mut().resource_mut::<BoardOptions>();
+        board_options.map_size = (30, 16);
+        board_options.bomb_count = 99;
+        app.update();
+        let settings = app.world().resource::<Settings>();
+        assert_eq!((settings.board.map_size, settings.board.bomb_count), ((30, 16), 99));
+    }
+
+    #[test]
+    fn challenge_boards_are_not_saved() {
+        let mut app = settings_app(Settings::default().board);
+        app.update();
+        let mut challenge = DailyChallenge::today();
+        challenge.apply(&mut app.world_mut().resource_mut::<BoardOptions>());
+        app.insert_resource(challenge);
+        app.update();
+        let settings = app.world().resource::<Settings>();
+        assert_eq!(settings.board.map_size, Settings::default().board.map_size);
+    }
+}